@@ -1,25 +1,49 @@
-use syn::{BinOp, Block, Expr, ExprArray, ExprBinary, ExprCall, ExprCast, ExprIf, ExprIndex, ExprMatch, ExprParen, ExprPath, ExprReference, ExprTuple, ExprUnary, LitInt, parse_quote, Pat, Path, Stmt, Type};
-use crate::ast::optimizable_function::{OptimizableRecursiveFunction, try_get_ident, try_get_int_lit};
+use syn::{BinOp, Block, Expr, ExprArray, ExprBinary, ExprBlock, ExprCall, ExprCast, ExprClosure, ExprField, ExprIf, ExprIndex, ExprMatch, ExprMethodCall, ExprParen, ExprPath, ExprRange, ExprReference, ExprStruct, ExprTuple, ExprUnary, FieldValue, LitInt, Local, parse_quote, Pat, Path, Stmt, Type};
+use crate::ast::optimizable_function::{OptimizableRecursiveFunction, integer_type_range, try_get_ident, try_get_int_lit};
+use crate::ast::formula_evaluation::FormulaEvaluator;
+use crate::ast::coverage::Coverage;
+use crate::ast::termination::TerminationCheck;
+use crate::ast::linear_formula::LinearFormula;
+use crate::ast::recursive_calls::try_collect_calls_in_expr;
+use crate::options::OptimizeOptions;
 use std::collections::{BTreeMap, BTreeSet};
-use proc_macro_error::abort;
+use proc_macro_error::{abort, Diagnostic};
+use crate::diagnostics::{report, warning};
 use num::Integer;
-use syn::__private::Span; // TODO: is this bad?
+use proc_macro2::Span;
 use syn::punctuated::Punctuated;
+use syn::visit::Visit;
+use quote::quote;
+
+/// number of loop iterations which are simulated at compile time to find an overflow
+const OVERFLOW_SEARCH_LIMIT: usize = 10_000;
+
+/// number of arguments between two constants which are evaluated at compile time to check the later one
+const CONSISTENCY_WALK_LIMIT: i128 = 10_000;
 
 /// assume constants can fit in i128 and predecessor function uses steps which fit i128
 #[derive(Debug)]
 pub struct AbstractRecursiveMatchFunction {
     name: String,
     recursion_parameter: String,
-    constants: BTreeMap<i128, i128>,
+    /// value expression of every constant argument (an integer literal, or a tuple or struct
+    /// expression for a Copy return type which is not an integer)
+    constants: BTreeMap<i128, Expr>,
+    /// function values which are not given as constants but can be computed at compile time
+    derived_constants: BTreeMap<i128, i128>,
+    /// literal of every constant argument (used to point diagnostics at the match arm)
+    constant_literals: BTreeMap<i128, LitInt>,
+    /// constants which are allowed to differ from the recursive formula
+    formula_overrides: BTreeSet<i128>,
     recursive_expr: Box<Expr>,
-    _return_type: Box<Type>,
+    return_type: Box<Type>,
     arg_type: Box<Type>,
     /// eureka tuple contains elements which represents the ith predecessors
     /// (predecessor function=d) of n
     ///
     /// 0 = n, 1 = d(n), 2 = d(d(n)), ...
     eureka_tuple: Option<(BTreeSet<u128>, StepOperator, u128)>,
+    options: OptimizeOptions,
 }
 
 #[derive(Debug, Eq, PartialEq, Clone)]
@@ -48,34 +72,82 @@ impl AbstractRecursiveMatchFunction {
             abort!(recursion_parameter, "must be an identifier");
         };
         let constants = convert_constants(f.constants());
-        let recursive_expr = f.recursive_formula().clone();
+        let constant_literals = f.constants().iter()
+            .map(|(c, _)| (c.base10_parse().unwrap(), c.clone()))
+            .collect();
+        let formula_overrides = f.formula_overrides().iter()
+            .map(|c| c.base10_parse().unwrap())
+            .collect();
+        let recursive_expr = Box::new(f.recursive_formula().clone());
         Self {
             name,
             recursion_parameter,
             constants,
+            derived_constants: BTreeMap::new(),
+            constant_literals,
+            formula_overrides,
             recursive_expr,
             arg_type: f.input_args()[0].ty.clone(),
-            _return_type: f.return_type().clone(),
-            eureka_tuple: None
+            return_type: Box::new(f.return_type().clone()),
+            eureka_tuple: None,
+            options: f.options().clone()
         }
     }
 
+    /// checks without aborting whether the iterative transform fits the function,
+    /// returns the reason why it does not otherwise
+    pub fn check_applicable(f: &OptimizableRecursiveFunction) -> Result<(), String> {
+        if f.input_args().len() != 1 {
+            return Err("the function has more than one argument".to_string());
+        }
+        let parameter = match &*f.input_args()[0].pat {
+            Pat::Ident(i) => i.ident.to_string(),
+            p => return Err(format!("the argument `{}` is not an identifier", quote!(#p)))
+        };
+        let calls = try_collect_calls_in_expr(f.recursive_formula(), f.name())
+            .map_err(|e| format!("the expression `{}` is not supported", quote!(#e)))?;
+        let mut recursive_calls = vec![];
+        for call in &calls {
+            for arg in call.args.iter() {
+                recursive_calls.push(match arg {
+                    Expr::Binary(e) => try_recursive_call_from(e, &parameter).map_err(|e| e.to_string())?,
+                    _ => return Err(format!("the recursive call argument `{}` is not of the form {} - k", quote!(#arg), parameter))
+                });
+            }
+        }
+        if recursive_calls.is_empty() {
+            return Err("the formula has no recursive call".to_string());
+        }
+        if recursive_calls.iter().any(|c| c.operator != recursive_calls[0].operator) {
+            return Err("the recursive calls move in both directions".to_string());
+        }
+        let mut function = Self::create_from(f);
+        function.eureka_tuple = function.construct_eureka_tuple();
+        match &function.eureka_tuple {
+            Some((eureka_tuple, _, _)) if eureka_tuple_has_form(eureka_tuple) => {}
+            _ => return Err("no eureka tuple of the form (1, 2, ..., n) exists".to_string())
+        }
+        function.derived_constants = function.derive_missing_constants();
+        if function.find_start_tuples().is_empty() {
+            return Err("no constants fit the eureka tuple".to_string());
+        }
+        Ok(())
+    }
+
     /// convert the function body to iterative statements
     pub fn construct_iterative_stmts(&mut self) -> Vec<Stmt> {
         if self.eureka_tuple.is_none() {
             self.eureka_tuple = self.construct_eureka_tuple();
         }
         if let Some((eureka_tuple, step_operator, step_size)) = &self.eureka_tuple {
-            println!("eureka tuple: {:?} constants: {:?}", (eureka_tuple, step_operator, step_size), self.constants);
-            if eureka_tuple.len() > self.constants.len() {
-                // TODO when we have more constants that can be a problem (if it contradicts the recursive formula)
-                // example fib(0) = 0, fib(1) = 1, fib(2) = 8, fib(n) = fib(n-1) + fib(n-2)
-                // TODO: not enough constants can lead to some more results which can be pre-computed
-                abort!(self.recursive_expr, "Eureka tuple size is larger than constant size. Eureka tuple: {:?}", eureka_tuple)
-            }
+            println!("eureka tuple: {:?} constants: {:?}", (eureka_tuple, step_operator, step_size), self.constants.keys());
+            self.derived_constants = self.derive_missing_constants();
+            self.check_constant_consistency();
             let mut stmts = vec![];
             stmts.append(&mut self.create_constant_checks());
             let branches = self.get_initial_constants();
+            let fully_covered = self.check_coverage(&branches);
+            self.check_unused_constants(&branches);
             for branch in branches {
                 stmts.push(Stmt::Expr(Expr::If(ExprIf{
                     attrs: vec![],
@@ -83,23 +155,447 @@ impl AbstractRecursiveMatchFunction {
                     cond: self.get_branch_condition(&branch),
                     then_branch: Block {
                         brace_token: Default::default(),
-                        stmts: self.create_loop(&branch) },
+                        stmts: self.create_overflow_check(&branch).into_iter()
+                            .chain(self.create_loop(&branch))
+                            .collect() },
                     else_branch: None
                 })));
             }
-            stmts.push(parse_quote!(panic!("result for argument not defined");));
+            if self.options.backwards {
+                for branch in self.get_initial_constants() {
+                    stmts.push(self.create_backwards_branch(&branch));
+                }
+            }
+            if fully_covered {
+                stmts.push(parse_quote!(unreachable!("every argument is covered by a constant or a branch");));
+            } else {
+                // every recursive call moves in the same direction, so an argument which misses the constants
+                // can not return to itself, it leaves the domain of the argument type
+                let recursion_parameter = syn::Ident::new(&self.recursion_parameter, Span::call_site());
+                let direction = match step_operator {
+                    StepOperator::Sub => "below",
+                    StepOperator::Add => "above"
+                };
+                let arg_type = &self.arg_type;
+                let message = format!("argument {{}} does not reach a constant, the recursion steps {} the constants and leaves the domain of {}",
+                    direction, quote!(#arg_type));
+                stmts.push(parse_quote!(panic!(#message, #recursion_parameter);));
+            }
             stmts
         } else {
             abort!(self.recursive_expr, "could not find eureka tuple");
         }
     }
 
+    /// the integer values of the constants, empty if the return type is not an integer
+    fn constant_numbers(&self) -> BTreeMap<i128, i128> {
+        self.constants.iter()
+            .filter_map(|(c, v)| Some((*c, try_get_int_lit(v)?.base10_parse().ok()?)))
+            .collect()
+    }
+
+    /// the integer values of all constants given in the match and the ones derived from them
+    fn known_constants(&self) -> BTreeMap<i128, i128> {
+        let mut known = self.derived_constants.clone();
+        known.extend(self.constant_numbers());
+        known
+    }
+
+    /// all arguments with a constant given in the match or derived from them
+    fn known_arguments(&self) -> BTreeSet<i128> {
+        self.constants.keys().chain(self.derived_constants.keys()).copied().collect()
+    }
+
+    /// the value expression of a known argument
+    fn constant_value(&self, argument: &i128) -> Expr {
+        match self.constants.get(argument) {
+            Some(value) => value.clone(),
+            None => {
+                let value = parse_non_typed_int(&self.derived_constants[argument]);
+                parse_quote!(#value)
+            }
+        }
+    }
+
+    /// the change of the argument from one loop iteration to the next
+    /// (the loop counts up for sub steps and down for add steps)
+    fn loop_step(&self) -> Option<i128> {
+        match self.eureka_tuple.as_ref()? {
+            (_, StepOperator::Sub, step_size) => Some(*step_size as i128),
+            (_, StepOperator::Add, step_size) => Some(-(*step_size as i128))
+        }
+    }
+
+    /// evaluate the recursive formula at compile time for the window slots next to the constants
+    /// which are not given but whose predecessors are known
+    ///
+    /// example: 1 => 1, 3 => 3, 5 => 5, 6 => 6, _ => f(n - 1) + f(n - 3) can compute 4
+    ///
+    /// only the slots a start tuple can need are derived (the arguments of a whole eureka tuple
+    /// behind every constant), so a single constant can start the loop if the formula guards its calls
+    /// (0 => 1, _ => if n >= 2 { f(n - 2) } else { 0 } + f(n - 1) computes 1), while the arguments
+    /// between constants far apart are never enumerated
+    fn derive_missing_constants(&self) -> BTreeMap<i128, i128> {
+        let (window, step) = match (&self.eureka_tuple, self.loop_step()) {
+            (Some((eureka_tuple, _, _)), Some(step)) => (eureka_tuple.len(), step),
+            _ => return BTreeMap::new()
+        };
+        let (arg_min, arg_max) = integer_type_range(&self.arg_type).unwrap_or((i128::MIN, i128::MAX));
+        let mut slots = BTreeSet::new();
+        for c in self.constants.keys() {
+            let mut n = *c;
+            for _ in 1..window {
+                match n.checked_add(step) {
+                    Some(next) if next >= arg_min && next <= arg_max => n = next,
+                    _ => break
+                }
+                slots.insert(n);
+            }
+        }
+        // predecessors have to be computed first (add: arguments above n, sub: arguments below n)
+        let mut arguments: Vec<i128> = slots.into_iter().collect();
+        if step < 0 {
+            arguments.reverse();
+        }
+        let mut known = self.constant_numbers();
+        let mut evaluator = FormulaEvaluator::new(&self.name, &self.recursion_parameter);
+        // a derived value has to fit the return type, it is returned as a literal
+        if let Some(bounds) = integer_type_range(&self.return_type) {
+            evaluator = evaluator.with_bounds(bounds);
+        }
+        let mut derived = BTreeMap::new();
+        for n in arguments {
+            if known.contains_key(&n) || self.constants.contains_key(&n) {
+                continue
+            }
+            if let Some(value) = evaluator.evaluate(&self.recursive_expr, n, &known) {
+                known.insert(n, value);
+                derived.insert(n, value);
+            }
+        }
+        derived
+    }
+
+    /// warn about constants which differ from the value the recursive formula yields
+    /// for them (unless the arm is marked with #[override_formula])
+    ///
+    /// example: 0 => 0, 1 => 1, 5 => 1, _ => f(n - 1) + f(n - 2) would compute 5 for 5
+    ///
+    /// the arguments between a constant and the previous constant of its branch are evaluated
+    /// first, constants further apart than CONSISTENCY_WALK_LIMIT steps are not checked
+    fn check_constant_consistency(&self) {
+        let step = match self.loop_step() {
+            Some(step) => step,
+            None => return
+        };
+        let evaluator = FormulaEvaluator::new(&self.name, &self.recursion_parameter);
+        let known = self.known_constants();
+        for (c, v) in &self.constant_numbers() {
+            if self.formula_overrides.contains(c) {
+                continue
+            }
+            let mut predecessors = known.clone();
+            predecessors.remove(c);
+            let previous = self.constants.keys()
+                .filter(|p| (*c - *p) % step == 0 && (*c - *p) / step > 0 && (*c - *p) / step <= CONSISTENCY_WALK_LIMIT)
+                .min_by_key(|p| (*c - *p) / step);
+            if let Some(previous) = previous {
+                let mut n = *previous + step;
+                while n != *c {
+                    if !predecessors.contains_key(&n) {
+                        match evaluator.evaluate(&self.recursive_expr, n, &predecessors) {
+                            Some(value) => { predecessors.insert(n, value); }
+                            None => break
+                        }
+                    }
+                    n += step;
+                }
+            }
+            // a guarded formula which reads no earlier value does not derive the constant
+            // (0 => 1, _ => if n >= 1 { f(n - 1) } else { 0 } yields 0 for 0)
+            if evaluator.dependencies(&self.recursive_expr, *c, &predecessors).is_empty() {
+                continue
+            }
+            if let Some(computed) = evaluator.evaluate(&self.recursive_expr, *c, &predecessors) {
+                if computed != *v {
+                    warning!(
+                        self.constant_literals[c],
+                        "constant {} => {} contradicts the recursive formula which yields {}; help: mark the arm with #[override_formula] if this is intended",
+                        c, v, computed
+                    );
+                }
+            }
+        }
+    }
+
+    /// report constants which are never used by the recursion: they are neither part of
+    /// a start tuple, nor reached inside a loop, nor needed to derive a start tuple value
+    ///
+    /// example: 100 => 1, 102 => 2, 104 => 3, 99 => 9, _ => f(n + 4) + f(n + 2) + f(n + 6) - 1
+    /// never uses 99
+    fn check_unused_constants(&self, branches: &[Vec<i128>]) {
+        let (step_operator, step_size) = match &self.eureka_tuple {
+            Some((_, step_operator, step_size)) => (step_operator, *step_size as i128),
+            None => return
+        };
+        let evaluator = FormulaEvaluator::new(&self.name, &self.recursion_parameter);
+        let known = self.known_constants();
+        let mut used = BTreeSet::new();
+        let mut pending: Vec<i128> = branches.iter().flatten().copied().collect();
+        while let Some(c) = pending.pop() {
+            if used.insert(c) && self.derived_constants.contains_key(&c) {
+                let mut predecessors = known.clone();
+                predecessors.remove(&c);
+                pending.append(&mut evaluator.dependencies(&self.recursive_expr, c, &predecessors));
+            }
+        }
+        for c in self.constants.keys() {
+            if used.contains(c) {
+                continue
+            }
+            let branch = branches.iter()
+                .map(|b| *b.last().expect("need at least 1 start constant"))
+                .find(|start| (c - start) % step_size == 0);
+            match (branch, step_operator) {
+                (Some(start), StepOperator::Sub) if *c > start => {}
+                (Some(start), StepOperator::Add) if *c < start => {}
+                (Some(start), _) => {
+                    report!(at self.constant_literals[c],
+                        "constant {} lies behind the start constant {} of its branch and is never used by the recursion; note: it only answers the argument {} itself",
+                        c, start, c);
+                }
+                (None, _) => {
+                    report!(at self.constant_literals[c],
+                        "constant {} is never used by the recursion; note: it only answers the argument {} itself", c, c);
+                }
+            }
+        }
+    }
+
+    /// report the arguments of the parameter type which are not covered by a constant or a branch
+    /// (compile error with option `total`)
+    ///
+    /// returns true if every argument is covered
+    fn check_coverage(&self, branches: &[Vec<i128>]) -> bool {
+        let (domain, (_, step_operator, step_size)) = match (integer_type_range(&self.arg_type), &self.eureka_tuple) {
+            (Some(domain), Some(eureka_tuple)) => (domain, eureka_tuple),
+            _ => return false
+        };
+        let mut coverage = Coverage::new(domain, *step_size);
+        for c in self.known_arguments() {
+            coverage.add_constant(c);
+        }
+        for branch in branches {
+            let start_constant = *branch.last().expect("need at least 1 start constant");
+            coverage.add_branch(start_constant, *step_operator == StepOperator::Sub);
+            if self.options.backwards {
+                coverage.add_branch(branch[0], false);
+            }
+        }
+        let uncovered = coverage.uncovered();
+        if uncovered.is_empty() {
+            return true;
+        }
+        let listed: Vec<String> = uncovered.iter().take(10).map(|r| r.to_string()).collect();
+        let more = if uncovered.len() > 10 { format!(" and {} more", uncovered.len() - 10) } else { String::new() };
+        let termination = TerminationCheck::new(
+            *step_operator == StepOperator::Sub, *step_size, self.known_arguments().into_iter().collect());
+        // one reason for every listed range, ranges of the same residue class usually share it
+        let mut reasons: Vec<String> = vec![];
+        for range in uncovered.iter().take(10) {
+            let reason = termination.divergence_reason(range);
+            if !reasons.contains(&reason) {
+                reasons.push(reason);
+            }
+        }
+        let reason = reasons.join("; ");
+        if self.options.total {
+            abort!(self.arg_type, "the recursion does not reach a constant for arguments: {}{}", listed.join(", "), more;
+                note = "{}", reason);
+        }
+        report!(at self.arg_type,
+            "the recursion does not reach a constant for arguments: {}{}; note: {}; help: these arguments panic at runtime, use #[optimize_recursion(total)] to reject them at compile time",
+            listed.join(", "), more, reason);
+        false
+    }
+
+    /// simulate the loop of a branch at compile time and return the first argument
+    /// whose result does not fit into the return type
+    fn find_overflow(&self, constants: &[i128]) -> Option<i128> {
+        let bounds = integer_type_range(&self.return_type)?;
+        let (_, step_operator, step_size) = self.eureka_tuple.as_ref()?;
+        let step = match step_operator {
+            StepOperator::Add => -(*step_size as i128),
+            StepOperator::Sub => *step_size as i128
+        };
+        let domain = integer_type_range(&self.arg_type).unwrap_or((i128::MIN, i128::MAX));
+        let evaluator = FormulaEvaluator::new(&self.name, &self.recursion_parameter);
+        let bounded_evaluator = FormulaEvaluator::new(&self.name, &self.recursion_parameter).with_bounds(bounds);
+        let mut known = self.known_constants();
+        let mut n = *constants.last().expect("need at least 1 start constant");
+        for _ in 0..OVERFLOW_SEARCH_LIMIT {
+            n = n.checked_add(step)?;
+            if n < domain.0 || n > domain.1 {
+                return None;
+            }
+            if self.constants.contains_key(&n) {
+                continue
+            }
+            let value = evaluator.evaluate(&self.recursive_expr, n, &known)?;
+            if bounded_evaluator.evaluate(&self.recursive_expr, n, &known).is_none() {
+                return Some(n);
+            }
+            known.insert(n, value);
+        }
+        None
+    }
+
+    /// report the first overflowing argument of a branch and create a check for it (option overflow_check)
+    fn create_overflow_check(&self, constants: &[i128]) -> Option<Stmt> {
+        let overflow = self.find_overflow(constants)?;
+        let (_, step_operator, _) = self.eureka_tuple.as_ref()?;
+        let return_type = &self.return_type;
+        let start_constant = constants.last().expect("need at least 1 start constant");
+        let comparison = match step_operator {
+            StepOperator::Add => "<=",
+            StepOperator::Sub => ">="
+        };
+        if !self.options.overflow_check {
+            report!(at self.return_type,
+                "{} overflows {} for arguments {} {} (branch starting at {}); help: use #[optimize_recursion(overflow_check)] to panic with a precise message for these arguments",
+                self.name, quote!(#return_type), comparison, overflow, start_constant);
+            return None;
+        }
+        let recursion_parameter = syn::Ident::new(&self.recursion_parameter, Span::call_site());
+        let overflow_lit = parse_non_typed_int(&overflow);
+        let message = format!("{} overflows {} for arguments {} {}", self.name, quote!(#return_type), comparison, overflow);
+        Some(match step_operator {
+            StepOperator::Add => parse_quote!{
+                if #recursion_parameter <= #overflow_lit {
+                    panic!(#message);
+                }
+            },
+            StepOperator::Sub => parse_quote!{
+                if #recursion_parameter >= #overflow_lit {
+                    panic!(#message);
+                }
+            }
+        })
+    }
+
+    /// create a branch for arguments below the lowest constant of a start tuple
+    /// which runs the inverted recursive formula downwards (option backwards)
+    ///
+    /// example: f(n) = f(n - 1) + f(n - 2) is inverted to f(m) = f(m + 2) - f(m + 1)
+    fn create_backwards_branch(&self, constants: &[i128]) -> Stmt {
+        let (eureka_tuple, step_size) = match &self.eureka_tuple {
+            Some((eureka_tuple, StepOperator::Sub, step_size)) => (eureka_tuple, *step_size),
+            _ => abort!(self.recursive_expr, "option backwards needs recursive calls with decreasing arguments (n - k)")
+        };
+        let tuple_len = eureka_tuple.len();
+        let start_index = tuple_len - 1;
+        let lowest_constant = parse_non_typed_int(&constants[0]);
+        let recursion_parameter = syn::Ident::new(&self.recursion_parameter, Span::call_site());
+        let arg_type = &self.arg_type;
+        let step_size_lit = parse_non_typed_int(&(step_size as i128));
+        let mut stmts: Vec<Stmt> = vec![parse_quote!{
+            let mut tuple = [0; #tuple_len];
+        }];
+        // the index grows while the argument gets smaller
+        for (i, constant) in constants.iter().rev().enumerate() {
+            let value = self.constant_value(constant);
+            stmts.push(parse_quote!{
+                tuple[#i] = #value;
+            });
+        }
+        let current_argument: Expr = parse_quote!(#lowest_constant - ((i - #start_index) as #arg_type) * #step_size_lit);
+        let expr = self.create_inverted_formula(step_size, tuple_len);
+        stmts.push(parse_quote!{
+            let mut i: usize = #start_index;
+        });
+        stmts.push(parse_quote!{
+            while #current_argument != #recursion_parameter {
+                i += 1;
+                tuple[i % #tuple_len] = #expr;
+            }
+        });
+        stmts.push(parse_quote!{
+            return tuple[i % #tuple_len];
+        });
+        let cond: Expr = if step_size == 1 {
+            parse_quote!(#recursion_parameter < #lowest_constant)
+        } else {
+            parse_quote!(#recursion_parameter < #lowest_constant && (#lowest_constant - #recursion_parameter) % #step_size_lit == 0)
+        };
+        parse_quote!{
+            if #cond {
+                #(#stmts)*
+            }
+        }
+    }
+
+    /// solve the linear recursive formula for the farthest recursive call and
+    /// express it with tuple accesses of a loop running downwards
+    fn create_inverted_formula(&self, step_size: u128, tuple_len: usize) -> Expr {
+        let recursive_steps = |call: &ExprCall| -> Option<u128> {
+            if call_name(call) != self.name || call.args.len() != 1 {
+                return None;
+            }
+            match call.args.first()? {
+                Expr::Binary(e) => Some(recursive_call_from(e, &self.recursion_parameter).steps / step_size),
+                _ => None
+            }
+        };
+        let formula = match LinearFormula::from_expr(&self.recursive_expr, &recursive_steps) {
+            Some(formula) => formula,
+            None => abort!(self.recursive_expr, "option backwards needs a recursive formula which is linear in the recursive calls";
+                note = "allowed are integer literals, recursive calls, +, - and multiplication with integer literals")
+        };
+        let farthest = formula.farthest_step().expect("need at least 1 recursive call");
+        let coefficient = formula.coefficients[&farthest];
+        if coefficient.abs() != 1 {
+            abort!(self.recursive_expr, "option backwards can not invert the coefficient {} of the farthest recursive call", coefficient;
+                note = "only the coefficients 1 and -1 have an integer inverse");
+        }
+        // f(m) = (f(m + K) - sum of a_k * f(m + K - k) - c) / a_K with K = farthest step
+        let farthest_index = parse_non_typed_int(&(farthest as i128));
+        let mut expr: Expr = parse_quote!(tuple[(i - #farthest_index) % #tuple_len]);
+        for (k, a) in formula.coefficients.iter().filter(|(k, _)| **k != farthest) {
+            let index = parse_non_typed_int(&((farthest - k) as i128));
+            let factor = parse_non_typed_int(&a.abs());
+            expr = if *a > 0 {
+                parse_quote!(#expr - #factor * tuple[(i - #index) % #tuple_len])
+            } else {
+                parse_quote!(#expr + #factor * tuple[(i - #index) % #tuple_len])
+            };
+        }
+        if formula.constant != 0 {
+            let constant = parse_non_typed_int(&formula.constant.abs());
+            expr = if formula.constant > 0 {
+                parse_quote!(#expr - #constant)
+            } else {
+                parse_quote!(#expr + #constant)
+            };
+        }
+        if coefficient < 0 {
+            expr = parse_quote!(-(#expr));
+        }
+        expr
+    }
+
     /// return the condition for specific starting constants that will yield a result for parameter n
-    fn get_branch_condition(&self, constants: &Vec<i128>) -> Box<Expr> {
+    fn get_branch_condition(&self, constants: &[i128]) -> Box<Expr> {
         let start_constant = constants.last().expect("need at least 1 start constant");
         let start_constant = parse_non_typed_int(start_constant);
         let recursion_parameter: syn::Ident = syn::Ident::new(&self.recursion_parameter, Span::call_site());
         if let Some((_, step_operator, step_size)) = &self.eureka_tuple {
+            if *step_size == 1 {
+                // every argument is in the same residue class
+                return match step_operator {
+                    StepOperator::Add => Box::new(parse_quote!(#start_constant >= #recursion_parameter)),
+                    StepOperator::Sub => Box::new(parse_quote!(#start_constant <= #recursion_parameter))
+                };
+            }
             let step_size = parse_non_typed_int(&(*step_size as i128));
             match step_operator {
                 StepOperator::Add => {
@@ -139,10 +635,10 @@ impl AbstractRecursiveMatchFunction {
 
     /// find all recursive calls in the expression and calculate common step operate and size (via gcd)
     fn find_recursive_calls(&self) -> (Vec<RecursiveCall>, StepOperator, u128) {
-        let recursive_calls: Vec<_> = find_calls(&self.recursive_expr)
-            .into_iter()
-            .filter(|e| call_name(e).as_str().eq(&self.name))
-            .collect();
+        let recursive_calls = match try_collect_calls_in_expr(&self.recursive_expr, &self.name) {
+            Ok(calls) => calls,
+            Err(e) => abort!(e, "expression {:?} not supported!", e)
+        };
         let mut result = vec![];
         for call in recursive_calls.iter() {
             for arg in call.args.iter() {
@@ -152,15 +648,17 @@ impl AbstractRecursiveMatchFunction {
                 });
             }
         }
-        if result.len() == 0 {
+        if result.is_empty() {
             abort!(self.recursive_expr, "need at least 1 recursive call!");
         }
         // check if all recursive calls have the same operator
         let common_operator = result[0].operator.clone();
-        for c in &result {
-            if c.operator != common_operator {
-                abort!(self.recursive_expr, "recursive step operator must be the same for all recursive calls!");
-            }
+        if let Some(other) = result.iter().find(|c| c.operator != common_operator) {
+            let (down, up) = if common_operator == StepOperator::Sub { (&result[0], other) } else { (other, &result[0]) };
+            abort!(self.recursive_expr, "recursion is not well-founded: {}({} - {}) moves to smaller arguments while {}({} + {}) moves to larger ones",
+                self.name, self.recursion_parameter, down.steps, self.name, self.recursion_parameter, up.steps;
+                note = "an argument which is not a constant can step down and up again and reach itself, so the original recursion does not terminate";
+                help = "all recursive calls must either decrease or increase the argument");
         }
         let mut gcd = result[0].steps;
         for c in &result {
@@ -170,18 +668,18 @@ impl AbstractRecursiveMatchFunction {
             c.steps /= gcd;
             c.common_step_size = gcd;
         }
-        result.sort_by(|a, b| a.steps.cmp(&b.steps));
+        result.sort_by_key(|a| a.steps);
 
         // println!("converted recursive calls: {:?}", result);
         (result, common_operator, gcd)
     }
 
-    /// if n is one of the constants given return the value immediately
+    /// if n is one of the constants given (or derived) return the value immediately
     fn create_constant_checks(&self) -> Vec<Stmt> {
         let mut result = vec![];
-        for (c, v) in &self.constants {
+        for c in &self.known_arguments() {
+            let v = self.constant_value(c);
             let c = parse_non_typed_int(c);
-            let v = parse_non_typed_int(v);
             let parameter = syn::Ident::new(&self.recursion_parameter, Span::call_site());
             result.push(parse_quote!{
                 if #parameter == #c {
@@ -193,26 +691,17 @@ impl AbstractRecursiveMatchFunction {
     }
 
     /// create a tuple containing the last calculated function values starting with given constants
-    fn create_tmp_tuple(&self, constants: &Vec<i128>) -> Vec<Stmt> {
-        // reverse when using add step operator
-        let mut result = vec![];
-        let default = parse_non_typed_int(&0);
-        let len = constants.len();
-        result.push(parse_quote!{
-            let mut tuple = [#default;#len];
-        });
-        // could use a for loop instead
-        for (i, constant) in constants.iter().enumerate() {
-            let constant = parse_non_typed_int(&self.constants[constant]);
-            result.push(parse_quote!{
-                tuple[#i] = #constant;
-            });
-        }
-        result
+    ///
+    /// the values are written as an array literal, so the return type does not need a default value
+    fn create_tmp_tuple(&self, constants: &[i128]) -> Vec<Stmt> {
+        let values = constants.iter().map(|constant| self.constant_value(constant));
+        vec![parse_quote!{
+            let mut tuple = [#(#values),*];
+        }]
     }
 
     /// create while loop calculating n with given start constants
-    fn create_loop(&self, constants: &Vec<i128>) -> Vec<Stmt> {
+    fn create_loop(&self, constants: &[i128]) -> Vec<Stmt> {
         if let Some((eureka_tuple, step_operator, step_size)) = &self.eureka_tuple {
             let mut result = vec![];
             result.append(&mut self.create_tmp_tuple(constants));
@@ -234,15 +723,13 @@ impl AbstractRecursiveMatchFunction {
                     match step_operator {
                         StepOperator::Add => {
                             if c_a < start_constant {
-                                let c_a = parse_non_typed_int(&c_a);
-                                let c_v = parse_non_typed_int(&c_v);
+                                let c_a = parse_non_typed_int(c_a);
                                 arms.push(parse_quote!(#c_a => #c_v))
                             }
                         }
                         StepOperator::Sub => {
                             if c_a > start_constant {
-                                let c_a = parse_non_typed_int(&c_a);
-                                let c_v = parse_non_typed_int(&c_v);
+                                let c_a = parse_non_typed_int(c_a);
                                 arms.push(parse_quote!(#c_a => #c_v))
                             }
                         }
@@ -260,11 +747,20 @@ impl AbstractRecursiveMatchFunction {
             );
             arms.push(match_expr.arms.first().expect("there is one arm").clone());
             match_expr.arms = arms;
+            // the formula has to see the argument of the current iteration, not the requested one
+            let value: Expr = if uses_identifier(&self.recursive_expr, &self.recursion_parameter) {
+                parse_quote!({
+                    let #recursion_parameter: #arg_type = #current_argument;
+                    #match_expr
+                })
+            } else {
+                Expr::Match(match_expr)
+            };
 
             result.push(parse_quote!{
                 while #current_argument != #recursion_parameter {
                     i += 1;
-                    tuple[i % #tuple_len] = #match_expr;
+                    tuple[i % #tuple_len] = #value;
                 }
             });
             result.push(parse_quote!{
@@ -281,14 +777,30 @@ impl AbstractRecursiveMatchFunction {
     ///
     /// when there are multiple valid start tuple in the same branch use the most senior
     fn get_initial_constants(&self) -> Vec<Vec<i128>> {
-        if let Some((eureka_tuple, step_operator, step_size)) = &self.eureka_tuple {
-            let mut all_possible = vec![];
-            if !eureka_tuple.iter().enumerate().all(|(i, x)| i + 1 == *x as usize) {
+        if let Some((eureka_tuple, _, _)) = &self.eureka_tuple {
+            if !eureka_tuple_has_form(eureka_tuple) {
                 abort!(self.recursive_expr, "eureka tuple does not have form: (1,2,...n) tuple: {:?}", eureka_tuple)
             }
-            for c_start in self.constants.keys() {
+            let result = self.find_start_tuples();
+            if !result.is_empty() {
+                println!("result start tuples: {:?}", result);
+                result
+            } else {
+                abort!(self.recursive_expr, "could not find constants to fit the eureka tuple {:?} constants: {:?}", self.eureka_tuple, self.known_arguments())
+            }
+        } else {
+            abort!(self.recursive_expr, "eureka tuple not found!")
+        }
+    }
+
+    /// start tuples of known constants which fit the eureka tuple, one per residue class of the step size
+    fn find_start_tuples(&self) -> Vec<Vec<i128>> {
+        if let Some((eureka_tuple, step_operator, step_size)) = &self.eureka_tuple {
+            let mut all_possible = vec![];
+            let known = self.known_arguments();
+            for c_start in known.iter() {
                 let mut current = vec![];
-                for c in self.constants.keys() {
+                for c in known.iter() {
                     if c < c_start {
                         continue
                     }
@@ -309,7 +821,7 @@ impl AbstractRecursiveMatchFunction {
                 StepOperator::Add => {
                     for (i, t) in all_possible.iter().enumerate().rev() {
                         if !ignore.contains(&i) {
-                            result.push(t.iter().rev().map(|x| *x).collect());
+                            result.push(t.iter().rev().copied().collect());
                             for (oi, ot) in all_possible.iter().enumerate().rev() {
                                 if (t[0] - ot[0]) % *step_size as i128 == 0 {
                                     ignore.insert(oi);
@@ -332,20 +844,15 @@ impl AbstractRecursiveMatchFunction {
                 }
             };
 
-            if result.len() > 0 {
-                println!("result start tuples: {:?}", result);
-                result
-            } else {
-                abort!(self.recursive_expr, "could not find constants to fit the eureka tuple {:?} constants: {:?}", self.eureka_tuple, self.constants)
-            }
+            result
         } else {
-            abort!(self.recursive_expr, "eureka tuple not found!")
+            vec![]
         }
     }
 
     /// replace recursive calls with tuple access to already computed values
-    fn recursive_to_tuple_based_expr(&self, recursive_expr: &Box<Expr>) -> Box<Expr> {
-        match &**recursive_expr {
+    fn recursive_to_tuple_based_expr(&self, recursive_expr: &Expr) -> Box<Expr> {
+        match recursive_expr {
             Expr::Call(e) => {
                 if call_name(e).as_str().eq(&self.name) {
                     // recursive call
@@ -356,7 +863,7 @@ impl AbstractRecursiveMatchFunction {
                         Expr::Binary(eb) => {
                             let mut rc = recursive_call_from(eb, &self.recursion_parameter);
                             rc.common_step_size = self.eureka_tuple.as_ref().unwrap().2;
-                            rc.steps = rc.steps / rc.common_step_size;
+                            rc.steps /= rc.common_step_size;
                             self.create_tuple_expr(rc)
                         }
                         _ => {
@@ -375,7 +882,7 @@ impl AbstractRecursiveMatchFunction {
             Expr::Array(ExprArray{ attrs: at, bracket_token: t, elems }) => {
                 Box::new(Expr::Array(ExprArray{
                     attrs: at.clone(),
-                    bracket_token: t.clone(),
+                    bracket_token: *t,
                     elems: self.recursive_punctuated(elems)
                 }))
             }
@@ -383,7 +890,7 @@ impl AbstractRecursiveMatchFunction {
                 Box::new(Expr::Binary(ExprBinary{
                     attrs: at.clone(),
                     left: self.recursive_to_tuple_based_expr(left),
-                    op: op.clone(),
+                    op: *op,
                     right: self.recursive_to_tuple_based_expr(right)
                 }))
             }
@@ -391,25 +898,38 @@ impl AbstractRecursiveMatchFunction {
                 Box::new(Expr::Cast(ExprCast{
                     attrs: at.clone(),
                     expr: self.recursive_to_tuple_based_expr(expr),
-                    as_token: to.clone(),
+                    as_token: *to,
                     ty: ty.clone()
                 }))
             }
             Expr::Field(e) => {
-                Box::new(Expr::Field(e.clone()))
+                Box::new(Expr::Field(ExprField{
+                    base: self.recursive_to_tuple_based_expr(&e.base),
+                    ..e.clone()
+                }))
+            }
+            Expr::Struct(e) => {
+                Box::new(Expr::Struct(ExprStruct{
+                    fields: e.fields.iter().map(|field| FieldValue{
+                        expr: *self.recursive_to_tuple_based_expr(&field.expr),
+                        ..field.clone()
+                    }).collect(),
+                    rest: e.rest.as_ref().map(|rest| self.recursive_to_tuple_based_expr(rest)),
+                    ..e.clone()
+                }))
             }
             Expr::Index(ExprIndex{ attrs: at, expr, bracket_token: bt, index }) => {
                 Box::new(Expr::Index(ExprIndex{
                     attrs: at.clone(),
                     expr: self.recursive_to_tuple_based_expr(expr),
-                    bracket_token: bt.clone(),
+                    bracket_token: *bt,
                     index: self.recursive_to_tuple_based_expr(index)
                 }))
             }
             Expr::Paren(ExprParen{ attrs: at, paren_token: pt, expr }) => {
                 Box::new(Expr::Paren(ExprParen{
                     attrs: at.clone(),
-                    paren_token: pt.clone(),
+                    paren_token: *pt,
                     expr: self.recursive_to_tuple_based_expr(expr)
                 }))
             }
@@ -419,16 +939,16 @@ impl AbstractRecursiveMatchFunction {
             Expr::Reference(ExprReference{ attrs, and_token, raw, mutability, expr }) => {
                 Box::new(Expr::Reference(ExprReference{
                     attrs: attrs.clone(),
-                    and_token: and_token.clone(),
+                    and_token: *and_token,
                     raw: raw.clone(),
-                    mutability: mutability.clone(),
+                    mutability: *mutability,
                     expr: self.recursive_to_tuple_based_expr(expr)
                 }))
             }
             Expr::Tuple(ExprTuple{ attrs: at, paren_token: pt, elems }) => {
                 Box::new(Expr::Tuple(ExprTuple{
                     attrs: at.clone(),
-                    paren_token: pt.clone(),
+                    paren_token: *pt,
                     elems: self.recursive_punctuated(elems)
                 }))
             }
@@ -438,19 +958,69 @@ impl AbstractRecursiveMatchFunction {
             Expr::Unary(ExprUnary{ attrs: at, op, expr }) => {
                 Box::new(Expr::Unary(ExprUnary{
                     attrs: at.clone(),
-                    op: op.clone(),
+                    op: *op,
                     expr: self.recursive_to_tuple_based_expr(expr)
                 }))
             }
             Expr::Lit(l) => {
                 Box::new(Expr::Lit(l.clone()))
             }
+            Expr::MethodCall(e) => {
+                Box::new(Expr::MethodCall(ExprMethodCall{
+                    receiver: self.recursive_to_tuple_based_expr(&e.receiver),
+                    args: self.recursive_punctuated(&e.args),
+                    ..e.clone()
+                }))
+            }
+            Expr::Closure(e) => {
+                Box::new(Expr::Closure(ExprClosure{
+                    body: self.recursive_to_tuple_based_expr(&e.body),
+                    ..e.clone()
+                }))
+            }
+            Expr::Range(e) => {
+                Box::new(Expr::Range(ExprRange{
+                    from: e.from.as_ref().map(|from| self.recursive_to_tuple_based_expr(from)),
+                    to: e.to.as_ref().map(|to| self.recursive_to_tuple_based_expr(to)),
+                    ..e.clone()
+                }))
+            }
+            Expr::If(e) => {
+                Box::new(Expr::If(ExprIf{
+                    cond: self.recursive_to_tuple_based_expr(&e.cond),
+                    then_branch: self.recursive_block(&e.then_branch),
+                    else_branch: e.else_branch.as_ref()
+                        .map(|(else_token, else_branch)| (*else_token, self.recursive_to_tuple_based_expr(else_branch))),
+                    ..e.clone()
+                }))
+            }
+            Expr::Block(e) => {
+                Box::new(Expr::Block(ExprBlock{
+                    block: self.recursive_block(&e.block),
+                    ..e.clone()
+                }))
+            }
             _ => {
                 abort!(recursive_expr, "expression {:?} not supported! (tuple conversion)", recursive_expr);
             }
         }
     }
 
+    /// help function for recursive_to_tuple_based_expr:
+    /// will call recursive_to_tuple_based_expr for let initializers and expressions of a block
+    fn recursive_block(&self, block: &Block) -> Block {
+        let stmts = block.stmts.iter().map(|stmt| match stmt {
+            Stmt::Local(l) => Stmt::Local(Local{
+                init: l.init.as_ref().map(|(eq, init)| (*eq, self.recursive_to_tuple_based_expr(init))),
+                ..l.clone()
+            }),
+            Stmt::Expr(e) => Stmt::Expr(*self.recursive_to_tuple_based_expr(e)),
+            Stmt::Semi(e, semi) => Stmt::Semi(*self.recursive_to_tuple_based_expr(e), *semi),
+            Stmt::Item(i) => Stmt::Item(i.clone())
+        }).collect();
+        Block { brace_token: block.brace_token, stmts }
+    }
+
     /// help function for recursive_to_tuple_based_expr:
     /// will call recursive_to_tuple_based_expr for every element in Punctuated
     fn recursive_punctuated<T>(&self, pun: &Punctuated<Expr, T>) -> Punctuated<Expr, T>
@@ -458,7 +1028,7 @@ impl AbstractRecursiveMatchFunction {
         let mut p = pun.clone();
         p.clear();
         for el in pun.iter() {
-            p.push(*self.recursive_to_tuple_based_expr(&Box::new(el.clone())));
+            p.push(*self.recursive_to_tuple_based_expr(el));
         }
         p
     }
@@ -473,16 +1043,17 @@ impl AbstractRecursiveMatchFunction {
     }
 }
 
-/// convert constant literal so a map containing i128
-fn convert_constants(constants: &Vec<(LitInt, LitInt)>) -> BTreeMap<i128, i128> {
+/// convert the constant literals to a map from i128 to the value expression
+///
+/// integer values have to be parsed to i128 because the formula is evaluated at compile time
+fn convert_constants(constants: &[(LitInt, Expr)]) -> BTreeMap<i128, Expr> {
     let mut b = BTreeMap::new();
     for (c, v) in constants.iter() {
         if let Ok(constant) = c.base10_parse() {
-            if let Ok(value) = v.base10_parse() {
-                b.insert(constant, value);
-            } else {
+            if try_get_int_lit(v).is_some_and(|lit| lit.base10_parse::<i128>().is_err()) {
                 abort!(v, "constant value in constant literal can not be parsed to i128");
             }
+            b.insert(constant, v.clone());
         } else {
             abort!(c, "constant literal in match can not be parsed to i128");
         }
@@ -490,55 +1061,6 @@ fn convert_constants(constants: &Vec<(LitInt, LitInt)>) -> BTreeMap<i128, i128>
     b
 }
 
-/// collect all call expressions inside an expression
-fn find_calls(recursive_expr: &Box<Expr>) -> Vec<ExprCall> {
-    let mut result = vec![];
-    match &**recursive_expr {
-        Expr::Array(ExprArray{ attrs: _, bracket_token: _, elems }) => {
-            for el in elems.iter() {
-                result.append(&mut find_calls(&Box::new(el.clone())));
-            }
-        }
-        Expr::Binary(ExprBinary{ attrs: _, left, op: _, right }) => {
-            result.append(&mut find_calls(left));
-            result.append(&mut find_calls(right))
-        }
-        Expr::Call(e) => {
-            result.push(e.clone());
-        }
-        Expr::Cast(ExprCast{ attrs: _, expr, as_token: _, ty: _ }) => {
-            result.append(&mut find_calls(expr));
-        }
-        Expr::Field(_) => {}
-        Expr::Index(ExprIndex{ attrs: _, expr, bracket_token: _, index }) => {
-            result.append(&mut find_calls(expr));
-            result.append(&mut find_calls(index));
-        }
-        Expr::Paren(ExprParen{ attrs: _, paren_token: _, expr }) => {
-            result.append(&mut find_calls(expr));
-        }
-        Expr::Path(_) => {}
-        Expr::Reference(ExprReference{ attrs: _, and_token: _, raw: _, mutability: _, expr }) => {
-            result.append(&mut find_calls(expr));
-        }
-        Expr::Tuple(ExprTuple{ attrs: _, paren_token: _, elems }) => {
-            for el in elems.iter() {
-                result.append(&mut find_calls(&Box::new(el.clone())));
-            }
-        }
-        Expr::Type(_) => {}
-        Expr::Unary(ExprUnary{ attrs: _, op: _, expr }) => {
-            result.append(&mut find_calls(expr));
-        }
-        Expr::Lit(_) => {}
-        _ => {
-            abort!(recursive_expr, "expression {:?} not supported!", recursive_expr);
-        }
-    }
-    result
-}
-
-
 /// returns the called function name of an call expression
 fn call_name(f: &ExprCall) -> String {
     match &*f.func {
@@ -554,37 +1076,41 @@ fn call_name(f: &ExprCall) -> String {
 
 /// create a RecursiveCall struct with common_step_size = 1
 fn recursive_call_from(e: &ExprBinary, parameter: &str) -> RecursiveCall {
+    match try_recursive_call_from(e, parameter) {
+        Ok(call) => call,
+        Err(e) => Diagnostic::from(e).abort()
+    }
+}
+
+fn try_recursive_call_from(e: &ExprBinary, parameter: &str) -> syn::Result<RecursiveCall> {
     let operator = match e.op {
         BinOp::Add(_) => StepOperator::Add,
         BinOp::Sub(_) => StepOperator::Sub,
-        _ => abort!(e.op, "need a binary add or sub for recursive parameter!")
+        _ => return Err(syn::Error::new_spanned(e.op, "need a binary add or sub for recursive parameter!"))
     };
     let c: LitInt = {
         if let Some(lit) = try_get_int_lit(&e.left) {
             if operator == StepOperator::Sub {
-                abort!(e.op, "left side int literal with sub step operator not allowed")
+                return Err(syn::Error::new_spanned(e.op, "left side int literal with sub step operator not allowed"))
             }
             lit
         } else if let Some(lit) = try_get_int_lit(&e.right) {
             lit
         } else {
-            abort!(e, "left or right side of recursive expressions needs to be an integer constant")
+            return Err(syn::Error::new_spanned(e, "left or right side of recursive expressions needs to be an integer constant"))
         }
     };
     if !recursion_parameter_ok(e, parameter) {
-        abort!(e, "recursion parameter does not match")
+        return Err(syn::Error::new_spanned(e, "recursion parameter does not match"))
     }
-    if let Ok(steps) = c.base10_parse() {
-        if steps <= 0 {
-            abort!(c, "recursive step integer constant must be > 0")
-        }
-        RecursiveCall {
+    match c.base10_parse() {
+        Ok(0) => Err(syn::Error::new_spanned(c, "recursive step integer constant must be > 0")),
+        Ok(steps) => Ok(RecursiveCall {
             steps,
             operator,
             common_step_size: 1
-        }
-    } else {
-        abort!(c, "could not parse recursive step integer constant")
+        }),
+        Err(_) => Err(syn::Error::new_spanned(c, "could not parse recursive step integer constant"))
     }
 }
 
@@ -598,8 +1124,31 @@ fn recursion_parameter_ok(e: &ExprBinary, parameter: &str) -> bool {
     }
 }
 
+/// true if the identifier appears in expr
+fn uses_identifier(expr: &Expr, identifier: &str) -> bool {
+    struct IdentifierFinder<'a> {
+        identifier: &'a str,
+        found: bool,
+    }
+    impl<'a, 'ast> Visit<'ast> for IdentifierFinder<'a> {
+        fn visit_ident(&mut self, i: &'ast syn::Ident) {
+            if i == self.identifier {
+                self.found = true;
+            }
+        }
+    }
+    let mut finder = IdentifierFinder { identifier, found: false };
+    finder.visit_expr(expr);
+    finder.found
+}
+
+/// true if the eureka tuple is (1, 2, ..., n)
+fn eureka_tuple_has_form(eureka_tuple: &BTreeSet<u128>) -> bool {
+    eureka_tuple.iter().enumerate().all(|(i, x)| i + 1 == *x as usize)
+}
+
 /// convert a i128 reference to a non typed LitInt
-fn parse_non_typed_int(v: &i128) -> LitInt {
+pub fn parse_non_typed_int(v: &i128) -> LitInt {
     let value: LitInt = parse_quote!(#v);
     LitInt::new(value.base10_digits(), value.span())
 }
\ No newline at end of file
@@ -0,0 +1,298 @@
+use std::collections::BTreeMap;
+use proc_macro_error::abort;
+use syn::{Arm, Block, Expr, ExprCall, ExprClosure, ExprForLoop, ExprIf, ExprMethodCall, ExprPath, ExprWhile, Ident, Item, ItemConst, Pat, PatIdent, Stmt, parse_quote};
+use syn::fold::{self, Fold};
+use syn::visit::{self, Visit};
+use crate::ast::optimizable_function::{try_get_ident, try_get_int_lit};
+use crate::ast::recursive_calls::{collect_calls_in_expr, try_call_name};
+
+/// replaces const items of the function body (integers and arrays of integers) by their value,
+/// so the recursion can use them as step sizes: f(n - STEP)
+pub struct ConstInliner {
+    values: BTreeMap<String, Expr>,
+}
+
+impl ConstInliner {
+    pub fn new(items: &[ItemConst]) -> Self {
+        let mut values = BTreeMap::new();
+        for item in items {
+            if !Self::is_inlinable(item) {
+                abort!(item.expr, "const items need an integer literal or an array of integer literals");
+            }
+            values.insert(item.ident.to_string(), (*item.expr).clone());
+        }
+        Self { values }
+    }
+
+    /// the integer and integer array consts of a module, other consts are left alone
+    pub fn for_module(items: &[ItemConst]) -> Self {
+        let inlinable: Vec<ItemConst> = items.iter().filter(|item| Self::is_inlinable(item)).cloned().collect();
+        Self::new(&inlinable)
+    }
+
+    pub fn is_inlinable(item: &ItemConst) -> bool {
+        let is_integer = |e: &Expr| try_get_int_lit(e).is_some();
+        match &*item.expr {
+            Expr::Array(a) => a.elems.iter().all(is_integer),
+            e => is_integer(e)
+        }
+    }
+}
+
+/// the first const declared outside of the function which the recursion uses as a step size
+/// (f(n - STEP)) or as the array of offsets (COINS.iter().map(|&c| f(n - c))),
+/// the macro only sees the const items in the function body
+pub fn outside_const(formula: &Expr, name: &str) -> Option<Ident> {
+    let mut finder = OutsideConstFinder { name, found: None };
+    finder.visit_expr(formula);
+    finder.found
+}
+
+struct OutsideConstFinder<'a> {
+    name: &'a str,
+    found: Option<Ident>,
+}
+
+impl<'a> OutsideConstFinder<'a> {
+    fn find_in(&mut self, e: &Expr) {
+        let mut names = ConstNameFinder { found: None };
+        names.visit_expr(e);
+        if self.found.is_none() {
+            self.found = names.found;
+        }
+    }
+}
+
+impl<'a, 'ast> Visit<'ast> for OutsideConstFinder<'a> {
+    fn visit_expr_call(&mut self, call: &'ast ExprCall) {
+        if try_call_name(call).as_deref() == Some(self.name) {
+            for arg in &call.args {
+                self.find_in(arg);
+            }
+        }
+        visit::visit_expr_call(self, call);
+    }
+
+    fn visit_expr_method_call(&mut self, call: &'ast ExprMethodCall) {
+        if call.args.iter().any(|arg| !collect_calls_in_expr(arg, self.name).is_empty()) {
+            self.find_in(&call.receiver);
+        }
+        visit::visit_expr_method_call(self, call);
+    }
+}
+
+/// the first identifier named like a const (upper case)
+struct ConstNameFinder {
+    found: Option<Ident>,
+}
+
+impl<'ast> Visit<'ast> for ConstNameFinder {
+    fn visit_expr_path(&mut self, p: &'ast ExprPath) {
+        if let Some(ident) = p.path.get_ident() {
+            let name = ident.to_string();
+            if self.found.is_none() && name.chars().any(|c| c.is_ascii_uppercase())
+                && name.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_') {
+                self.found = Some(ident.clone());
+            }
+        }
+    }
+}
+
+impl ConstInliner {
+    /// fold with the names bound in a nested scope not being replaced
+    fn shadowed<T>(&mut self, names: Vec<String>, fold: impl FnOnce(&mut Self) -> T) -> T {
+        let values = self.values.clone();
+        for name in names {
+            self.values.remove(&name);
+        }
+        let result = fold(self);
+        self.values = values;
+        result
+    }
+}
+
+/// the identifiers a pattern binds
+fn bindings(pat: &Pat) -> Vec<String> {
+    struct BindingFinder {
+        names: Vec<String>,
+    }
+    impl<'ast> Visit<'ast> for BindingFinder {
+        fn visit_pat_ident(&mut self, p: &'ast PatIdent) {
+            self.names.push(p.ident.to_string());
+            visit::visit_pat_ident(self, p);
+        }
+    }
+    let mut finder = BindingFinder { names: vec![] };
+    finder.visit_pat(pat);
+    finder.names
+}
+
+/// a path is only replaced where no closure input, let, match arm, for or if let pattern
+/// and no const item of an inner block rebinds its name
+impl Fold for ConstInliner {
+    fn fold_expr(&mut self, e: Expr) -> Expr {
+        match &e {
+            Expr::Path(p) if p.path.get_ident().is_some() => {
+                match self.values.get(&p.path.segments[0].ident.to_string()) {
+                    Some(value) => value.clone(),
+                    None => e
+                }
+            }
+            _ => fold::fold_expr(self, e)
+        }
+    }
+
+    fn fold_expr_closure(&mut self, e: ExprClosure) -> ExprClosure {
+        let names = e.inputs.iter().flat_map(bindings).collect();
+        self.shadowed(names, |inliner| fold::fold_expr_closure(inliner, e))
+    }
+
+    fn fold_arm(&mut self, arm: Arm) -> Arm {
+        self.shadowed(bindings(&arm.pat), |inliner| fold::fold_arm(inliner, arm))
+    }
+
+    fn fold_expr_for_loop(&mut self, e: ExprForLoop) -> ExprForLoop {
+        let expr = Box::new(self.fold_expr(*e.expr));
+        let body = self.shadowed(bindings(&e.pat), |inliner| inliner.fold_block(e.body));
+        ExprForLoop { expr, body, ..e }
+    }
+
+    fn fold_expr_if(&mut self, e: ExprIf) -> ExprIf {
+        let names = match &*e.cond {
+            Expr::Let(l) => bindings(&l.pat),
+            _ => vec![]
+        };
+        let cond = Box::new(self.fold_expr(*e.cond));
+        let then_branch = self.shadowed(names, |inliner| inliner.fold_block(e.then_branch));
+        let else_branch = e.else_branch.map(|(else_token, branch)| (else_token, Box::new(self.fold_expr(*branch))));
+        ExprIf { cond, then_branch, else_branch, ..e }
+    }
+
+    fn fold_expr_while(&mut self, e: ExprWhile) -> ExprWhile {
+        let names = match &*e.cond {
+            Expr::Let(l) => bindings(&l.pat),
+            _ => vec![]
+        };
+        let cond = Box::new(self.fold_expr(*e.cond));
+        let body = self.shadowed(names, |inliner| inliner.fold_block(e.body));
+        ExprWhile { cond, body, ..e }
+    }
+
+    fn fold_block(&mut self, b: Block) -> Block {
+        // const items are visible in the whole block, let bindings from the next statement on
+        let names = b.stmts.iter().filter_map(|stmt| match stmt {
+            Stmt::Item(Item::Const(c)) => Some(c.ident.to_string()),
+            _ => None
+        }).collect();
+        self.shadowed(names, |inliner| {
+            let mut stmts = vec![];
+            for stmt in b.stmts {
+                match stmt {
+                    Stmt::Local(local) => {
+                        let local = inliner.fold_local(local);
+                        for name in bindings(&local.pat) {
+                            inliner.values.remove(&name);
+                        }
+                        stmts.push(Stmt::Local(local));
+                    }
+                    stmt => stmts.push(inliner.fold_stmt(stmt))
+                }
+            }
+            Block { stmts, ..b }
+        })
+    }
+}
+
+/// unrolls sums and products of iterator chains over arrays of integer literals
+///
+/// example: [1, 2, 5].iter().map(|&c| f(n - c)).sum() becomes (f(n - 1)) + (f(n - 2)) + (f(n - 5))
+pub struct IteratorUnroller;
+
+impl IteratorUnroller {
+    /// the elements of array.iter().map(closure) and the closure, None for other expressions
+    fn mapped_array(e: &ExprMethodCall) -> Option<(Vec<Expr>, &ExprClosure)> {
+        if e.method != "map" || e.args.len() != 1 {
+            return None;
+        }
+        let closure = match &e.args[0] {
+            Expr::Closure(c) if c.inputs.len() == 1 => c,
+            _ => return None
+        };
+        let mut receiver = &*e.receiver;
+        loop {
+            match receiver {
+                Expr::MethodCall(m) if m.args.is_empty()
+                    && ["iter", "into_iter", "copied", "cloned"].contains(&m.method.to_string().as_str()) => {
+                    receiver = &m.receiver;
+                }
+                Expr::Paren(p) => receiver = &p.expr,
+                Expr::Array(a) if a.elems.iter().all(|e| try_get_int_lit(e).is_some()) => {
+                    return Some((a.elems.iter().cloned().collect(), closure));
+                }
+                _ => return None
+            }
+        }
+    }
+}
+
+impl Fold for IteratorUnroller {
+    fn fold_expr(&mut self, e: Expr) -> Expr {
+        let e = fold::fold_expr(self, e);
+        let call = match &e {
+            Expr::MethodCall(call) if call.args.is_empty() && (call.method == "sum" || call.method == "product") => call,
+            _ => return e
+        };
+        let (elements, closure) = match &*call.receiver {
+            Expr::MethodCall(map) => match Self::mapped_array(map) {
+                Some(mapped) => mapped,
+                None => return e
+            },
+            _ => return e
+        };
+        let parameter = match binding_ident(&closure.inputs[0]) {
+            Some(parameter) => parameter,
+            None => return e
+        };
+        let terms: Vec<Expr> = elements.iter().map(|element| {
+            let body = Substitution { identifier: &parameter, value: element }.fold_expr((*closure.body).clone());
+            parse_quote!((#body))
+        }).collect();
+        let sum = call.method == "sum";
+        match terms.split_first() {
+            None if sum => parse_quote!(0),
+            None => parse_quote!(1),
+            Some((first, rest)) if sum => parse_quote!((#first #(+ #rest)*)),
+            Some((first, rest)) => parse_quote!((#first #(* #rest)*))
+        }
+    }
+}
+
+/// the identifier bound by a closure parameter like c, &c or &&c
+fn binding_ident(p: &Pat) -> Option<String> {
+    match p {
+        Pat::Ident(i) if i.by_ref.is_none() && i.subpat.is_none() => Some(i.ident.to_string()),
+        Pat::Reference(r) => binding_ident(&r.pat),
+        _ => None
+    }
+}
+
+/// replaces an identifier by a value
+pub struct Substitution<'a> {
+    identifier: &'a str,
+    value: &'a Expr,
+}
+
+impl<'a> Substitution<'a> {
+    pub fn new(identifier: &'a str, value: &'a Expr) -> Self {
+        Self { identifier, value }
+    }
+}
+
+impl<'a> Fold for Substitution<'a> {
+    fn fold_expr(&mut self, e: Expr) -> Expr {
+        if try_get_ident(&e).as_deref() == Some(self.identifier) && matches!(&e, Expr::Path(p) if p.path.get_ident().is_some()) {
+            return self.value.clone();
+        }
+        fold::fold_expr(self, e)
+    }
+}
@@ -0,0 +1,92 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// a set of arguments n with start <= n <= end and n ≡ residue (mod modulus)
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ArgumentRange {
+    pub start: i128,
+    pub end: i128,
+    pub modulus: u128,
+}
+
+impl fmt::Display for ArgumentRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.start == self.end {
+            write!(f, "{}", self.start)
+        } else if self.modulus == 1 {
+            write!(f, "{}..={}", self.start, self.end)
+        } else {
+            let residue = self.start.rem_euclid(self.modulus as i128);
+            write!(f, "{}..={} (n % {} == {})", self.start, self.end, self.modulus, residue)
+        }
+    }
+}
+
+/// covered arguments of a function: single constants and branches which reach
+/// every argument of a residue class above (sub) or below (add) the start constant
+pub struct Coverage {
+    domain: (i128, i128),
+    modulus: u128,
+    constants: BTreeSet<i128>,
+    /// (start constant, branch goes up)
+    branches: Vec<(i128, bool)>,
+}
+
+impl Coverage {
+    pub fn new(domain: (i128, i128), modulus: u128) -> Self {
+        Self { domain, modulus, constants: BTreeSet::new(), branches: vec![] }
+    }
+
+    pub fn add_constant(&mut self, c: i128) {
+        self.constants.insert(c);
+    }
+
+    pub fn add_branch(&mut self, start: i128, upwards: bool) {
+        self.branches.push((start, upwards));
+    }
+
+    /// all arguments of the domain which are neither a constant nor reached by a branch
+    pub fn uncovered(&self) -> Vec<ArgumentRange> {
+        let (min, max) = self.domain;
+        let m = self.modulus as i128;
+        let mut result = vec![];
+        for residue in 0..m {
+            // smallest and largest argument of the residue class inside the domain
+            let first = min + (residue - min.rem_euclid(m)).rem_euclid(m);
+            let last = max - (max.rem_euclid(m) - residue).rem_euclid(m);
+            if first > last {
+                continue
+            }
+            let (mut low, mut high) = (first, last);
+            for (start, upwards) in &self.branches {
+                if start.rem_euclid(m) != residue {
+                    continue
+                }
+                if *upwards {
+                    high = high.min(start.saturating_sub(m));
+                } else {
+                    low = low.max(start.saturating_add(m));
+                }
+            }
+            if low > high {
+                continue
+            }
+            // constants split the uncovered interval
+            let mut current = low;
+            for c in self.constants.range(low..=high) {
+                if c.rem_euclid(m) != residue {
+                    continue
+                }
+                if current < *c {
+                    result.push(ArgumentRange { start: current, end: c.saturating_sub(m), modulus: self.modulus });
+                }
+                current = c.saturating_add(m);
+            }
+            if current <= high && !self.constants.contains(&high) {
+                result.push(ArgumentRange { start: current, end: high, modulus: self.modulus });
+            }
+        }
+        result.sort_by_key(|r| r.start);
+        result
+    }
+}
@@ -0,0 +1,225 @@
+use std::collections::BTreeSet;
+use proc_macro_error::abort;
+use syn::{BinOp, Block, Expr, ExprCall, ExprMatch, FnArg, Ident, ItemFn, Pat, ReturnType, Stmt, Type, parse_quote};
+use syn::fold::Fold;
+use crate::ast::abstract_recursive_match::parse_non_typed_int;
+use crate::ast::optimizable_function::{check_receiver, integer_type_range, try_get_ident, try_get_int_lit};
+use crate::ast::recursive_calls::{collect_calls_in_expr, CallReplacer};
+use crate::ast::termination::below_start_check;
+use crate::options::OptimizeOptions;
+
+/// a function of an integer n and a state from a small finite domain (bool, the variants of a
+/// fieldless enum or a range declared with domain(..) in the attribute)
+///
+/// the function is a vector valued recurrence: the values of all states are computed together
+/// upwards from the smallest constant, a window holds the rows of the last arguments
+///
+/// example: strings(n, last_was_one) = strings(n - 1, false) + if last_was_one { 0 } else { strings(n - 1, true) }
+pub struct FiniteStateFunction {
+    name: String,
+    parameter: Ident,
+    arg_type: Box<Type>,
+    state: Ident,
+    state_type: Box<Type>,
+    /// the values of the state, their position is the index into a row of the window
+    states: Vec<Expr>,
+    /// position of the state in the parameters
+    state_position: usize,
+    return_type: Box<Type>,
+    body: ExprMatch,
+    constants: BTreeSet<i128>,
+    /// steps k of every call f(n - k, ..)
+    steps: Vec<u128>,
+    window_size: u128,
+    start: i128,
+}
+
+impl FiniteStateFunction {
+    /// true if the function has an integer parameter and a parameter of type bool
+    /// or with a domain given in the options
+    pub fn is_applicable(f: &ItemFn, options: &OptimizeOptions) -> bool {
+        let parameters: Vec<(Option<Ident>, &Type)> = f.sig.inputs.iter().filter_map(|arg| match arg {
+            FnArg::Typed(t) => Some((match &*t.pat {
+                Pat::Ident(i) => Some(i.ident.clone()),
+                _ => None
+            }, &*t.ty)),
+            FnArg::Receiver(_) => None
+        }).collect();
+        if parameters.len() != 2 {
+            return false;
+        }
+        let finite = |(ident, ty): &(Option<Ident>, &Type)| is_bool(ty)
+            || options.domains.iter().any(|(p, _)| Some(p) == ident.as_ref());
+        let integer = |(_, ty): &(Option<Ident>, &Type)| integer_type_range(ty).is_some();
+        (integer(&parameters[0]) && finite(&parameters[1])) || (finite(&parameters[0]) && integer(&parameters[1]))
+    }
+
+    pub fn create_from(f: &ItemFn, options: &OptimizeOptions) -> Self {
+        let name = f.sig.ident.to_string();
+        let mut parameters = vec![];
+        for arg in &f.sig.inputs {
+            match arg {
+                FnArg::Receiver(r) => check_receiver(r),
+                FnArg::Typed(t) => match &*t.pat {
+                    Pat::Ident(i) => parameters.push((i.ident.clone(), t.ty.clone())),
+                    p => abort!(p, "must be an identifier")
+                }
+            }
+        }
+        let domain = |(ident, ty): &(Ident, Box<Type>)| -> Option<Vec<Expr>> {
+            match options.domains.iter().find(|(p, _)| p == ident) {
+                Some((_, values)) => Some(values.clone()),
+                None if is_bool(ty) => Some(vec![parse_quote!(false), parse_quote!(true)]),
+                None => None
+            }
+        };
+        // the integer parameter is the recursion parameter unless it has a declared domain itself
+        let state_position = match (domain(&parameters[0]), domain(&parameters[1])) {
+            (_, Some(_)) if integer_type_range(&parameters[0].1).is_some() => 1,
+            _ => 0
+        };
+        let states = domain(&parameters[state_position]).expect("the state has a domain");
+        let (state, state_type) = parameters[state_position].clone();
+        let (parameter, arg_type) = parameters[1 - state_position].clone();
+        let return_type = match &f.sig.output {
+            ReturnType::Default => abort!(f.sig, "macro optimize_recursion needs a return value"),
+            ReturnType::Type(_, t) => t.clone()
+        };
+        let body = match f.block.stmts.as_slice() {
+            [Stmt::Expr(Expr::Match(m))] if try_get_ident(&m.expr).as_ref() == Some(&parameter.to_string()) => m.clone(),
+            _ => abort!(f.block, "function can only have one match statement on {}", parameter)
+        };
+        let mut constants = BTreeSet::new();
+        let mut formula = None;
+        for arm in &body.arms {
+            match &arm.pat {
+                Pat::Lit(p) => {
+                    match try_get_int_lit(&p.expr).and_then(|c| c.base10_parse::<i128>().ok()) {
+                        Some(c) => constants.insert(c),
+                        None => abort!(p, "must be an integer literal")
+                    };
+                    if let Some(call) = collect_calls_in_expr(&arm.body, &name).first() {
+                        abort!(call, "constants can not call the function, they are the start of the computation");
+                    }
+                }
+                Pat::Wild(_) if formula.is_none() => formula = Some(&arm.body),
+                Pat::Wild(_) => abort!(arm.pat, "match expression can not have multiple wildcard"),
+                _ => abort!(arm.pat, "match expression can only have constants and one wildcard")
+            }
+        }
+        let formula = formula.unwrap_or_else(|| abort!(body, "match expression must have a wildcard expression"));
+        let calls = collect_calls_in_expr(formula, &name);
+        let steps: Vec<u128> = calls.iter().map(|call| {
+            if call.args.len() != 2 {
+                abort!(call, "recursive call needs 2 arguments");
+            }
+            call_steps(&call.args[1 - state_position], &parameter)
+        }).collect();
+        let window_size = steps.iter().copied().max().unwrap_or_else(|| abort!(formula, "need at least 1 recursive call!"));
+        let start = match constants.iter().next() {
+            Some(start) => *start,
+            None => abort!(body, "match expression needs at least 1 constant")
+        };
+        let result = Self {
+            name, parameter, arg_type, state, state_type, states, state_position, return_type, body,
+            constants, steps, window_size, start
+        };
+        result.check_start();
+        result
+    }
+
+    /// every argument of the first window which is not a constant has to find its predecessors
+    /// inside the window (the computation can not start otherwise)
+    fn check_start(&self) {
+        for offset in 0..self.window_size {
+            let argument = self.start + offset as i128;
+            if self.constants.contains(&argument) {
+                continue
+            }
+            if let Some(steps) = self.steps.iter().find(|steps| **steps > offset) {
+                abort!(self.body.expr, "{}({}, ..) is not a constant and needs {}({}, ..) which lies below the smallest constant {}",
+                    self.name, argument, self.name, argument - *steps as i128, self.start;
+                    help = "add a constant for {}", argument);
+            }
+        }
+    }
+
+    /// convert the function body to statements computing the rows of all states up to the argument
+    pub fn construct_stmts(&self) -> Vec<Stmt> {
+        let parameter = &self.parameter;
+        let arg_type = &self.arg_type;
+        let state = &self.state;
+        let state_type = &self.state_type;
+        let return_type = &self.return_type;
+        let states = &self.states;
+        let state_count = self.states.len();
+        let window_size = self.window_size as usize;
+        let start = parse_non_typed_int(&self.start);
+        let indices = 0..state_count;
+        let message = format!("{} is outside of the declared domain", state);
+        // bool is always covered, the declared domain can cover all variants of an enum as well
+        let outside: Option<syn::Arm> = if is_bool(state_type) {
+            None
+        } else {
+            Some(parse_quote!(#[allow(unreachable_patterns)] _ => panic!(#message),))
+        };
+        let body = self.replace_recursive_calls();
+        let below_start = below_start_check(parameter, arg_type, self.start);
+        let block: Block = parse_quote! {
+            {
+                #below_start
+                let state_index = |#state: #state_type| -> usize {
+                    match #state {
+                        #(#states => #indices,)*
+                        #outside
+                    }
+                };
+                // early returns of the formula only leave the evaluation of one state
+                let evaluate = |#parameter: #arg_type, #state: #state_type, window: &[[#return_type; #state_count]; #window_size], i: usize| -> #return_type #body;
+                let mut window: [[#return_type; #state_count]; #window_size] = [[Default::default(); #state_count]; #window_size];
+                let mut argument: #arg_type = #start;
+                let mut i: usize = 0;
+                loop {
+                    let row: [#return_type; #state_count] = [#(#states),*].map(|#state| evaluate(argument, #state, &window, i));
+                    window[i % #window_size] = row;
+                    if argument == #parameter {
+                        return window[i % #window_size][state_index(#state)];
+                    }
+                    argument += 1;
+                    i += 1;
+                }
+            }
+        };
+        block.stmts
+    }
+
+    /// rewrite the match: f(n - k, s) reads the state s from the row of n - k
+    fn replace_recursive_calls(&self) -> Block {
+        let window_size = self.window_size as usize;
+        let mut replacer = CallReplacer::new(&self.name, |call: ExprCall| {
+            let steps = call_steps(&call.args[1 - self.state_position], &self.parameter) as usize;
+            let state = &call.args[self.state_position];
+            parse_quote!(window[(i - #steps) % #window_size][state_index(#state)])
+        });
+        let body = replacer.fold_expr_match(self.body.clone());
+        parse_quote!({ #body })
+    }
+}
+
+fn is_bool(ty: &Type) -> bool {
+    matches!(ty, Type::Path(p) if p.path.is_ident("bool"))
+}
+
+/// steps k of an argument n - k
+fn call_steps(arg: &Expr, parameter: &Ident) -> u128 {
+    let steps = match arg {
+        Expr::Binary(e) if matches!(e.op, BinOp::Sub(_)) && try_get_ident(&e.left) == Some(parameter.to_string()) => {
+            try_get_int_lit(&e.right).and_then(|k| k.base10_parse::<u128>().ok())
+        }
+        _ => None
+    };
+    match steps {
+        Some(steps) if steps > 0 => steps,
+        _ => abort!(arg, "the argument needs the form {} - k with a constant k > 0", parameter)
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::BTreeMap;
+use syn::{BinOp, Block, Expr, ExprBinary, ExprCall, ExprCast, ExprIf, ExprParen, ExprUnary, Stmt, UnOp};
+use crate::ast::optimizable_function::{integer_type_range, try_get_ident, try_get_int_lit};
+
+/// evaluates a recursive formula at compile time for a concrete argument
+///
+/// recursive calls are looked up in the already known function values, conditions evaluate to 1 (true)
+/// or 0 (false), every expression that can not be evaluated (unknown function, overflow, ...) yields None
+#[derive(Clone, Copy)]
+pub struct FormulaEvaluator<'a> {
+    name: &'a str,
+    parameter: &'a str,
+    /// value range of the return type
+    bounds: Option<(i128, i128)>,
+    /// every intermediate result has to lie inside these bounds (value range of the current expression type)
+    expression_bounds: Option<(i128, i128)>,
+}
+
+impl<'a> FormulaEvaluator<'a> {
+    pub fn new(name: &'a str, parameter: &'a str) -> Self {
+        Self { name, parameter, bounds: None, expression_bounds: None }
+    }
+
+    /// treat every intermediate result outside of bounds as an overflow (result None)
+    ///
+    /// below a cast the bounds of the type of the cast expression are used instead, as far as it is known
+    pub fn with_bounds(mut self, bounds: (i128, i128)) -> Self {
+        self.bounds = Some(bounds);
+        self.expression_bounds = Some(bounds);
+        self
+    }
+
+    /// evaluate expr with the recursion parameter set to n
+    pub fn evaluate(&self, expr: &Expr, n: i128, known: &BTreeMap<i128, i128>) -> Option<i128> {
+        self.evaluate_recording(expr, n, known, &mut vec![])
+    }
+
+    /// arguments of the known values which are needed to evaluate expr for n
+    pub fn dependencies(&self, expr: &Expr, n: i128, known: &BTreeMap<i128, i128>) -> Vec<i128> {
+        let mut lookups = vec![];
+        self.evaluate_recording(expr, n, known, &mut lookups);
+        lookups
+    }
+
+    /// evaluate expr and record the arguments of all recursive calls in lookups
+    fn evaluate_recording(&self, expr: &Expr, n: i128, known: &BTreeMap<i128, i128>, lookups: &mut Vec<i128>) -> Option<i128> {
+        match expr {
+            Expr::Lit(_) => try_get_int_lit(expr)?.base10_parse().ok(),
+            Expr::Path(_) => {
+                if try_get_ident(expr)? == self.parameter {
+                    Some(n)
+                } else {
+                    None
+                }
+            }
+            Expr::Paren(ExprParen{ attrs: _, paren_token: _, expr }) => self.evaluate_recording(expr, n, known, lookups),
+            Expr::Cast(ExprCast{ attrs: _, expr, as_token: _, ty }) => {
+                let inner = Self { expression_bounds: self.bounds.and_then(|_| self.type_bounds(expr)), ..*self };
+                let value = inner.evaluate_recording(expr, n, known, lookups)?;
+                // integer casts wrap around instead of overflowing
+                match integer_type_range(ty) {
+                    Some((min, max)) => Some((value - min).rem_euclid(max - min + 1) + min),
+                    None => Some(value)
+                }
+            }
+            Expr::Unary(ExprUnary{ attrs: _, op: UnOp::Neg(_), expr }) => self.check_bounds(self.evaluate_recording(expr, n, known, lookups)?.checked_neg()?),
+            Expr::Unary(ExprUnary{ attrs: _, op: UnOp::Not(_), expr }) => Some((self.evaluate_recording(expr, n, known, lookups)? == 0) as i128),
+            Expr::Binary(ExprBinary{ attrs: _, left, op: BinOp::And(_), right }) => {
+                if self.evaluate_recording(left, n, known, lookups)? == 0 {
+                    Some(0)
+                } else {
+                    Some((self.evaluate_recording(right, n, known, lookups)? != 0) as i128)
+                }
+            }
+            Expr::Binary(ExprBinary{ attrs: _, left, op: BinOp::Or(_), right }) => {
+                if self.evaluate_recording(left, n, known, lookups)? != 0 {
+                    Some(1)
+                } else {
+                    Some((self.evaluate_recording(right, n, known, lookups)? != 0) as i128)
+                }
+            }
+            Expr::Binary(ExprBinary{ attrs: _, left, op, right }) => {
+                let left = self.evaluate_recording(left, n, known, lookups)?;
+                let right = self.evaluate_recording(right, n, known, lookups)?;
+                let result = match op {
+                    BinOp::Add(_) => left.checked_add(right),
+                    BinOp::Sub(_) => left.checked_sub(right),
+                    BinOp::Mul(_) => left.checked_mul(right),
+                    BinOp::Div(_) => left.checked_div(right),
+                    BinOp::Rem(_) => left.checked_rem(right),
+                    BinOp::Eq(_) => Some((left == right) as i128),
+                    BinOp::Ne(_) => Some((left != right) as i128),
+                    BinOp::Lt(_) => Some((left < right) as i128),
+                    BinOp::Le(_) => Some((left <= right) as i128),
+                    BinOp::Gt(_) => Some((left > right) as i128),
+                    BinOp::Ge(_) => Some((left >= right) as i128),
+                    _ => None
+                }?;
+                self.check_bounds(result)
+            }
+            Expr::Call(e) => self.evaluate_call(e, n, known, lookups),
+            Expr::If(ExprIf{ attrs: _, if_token: _, cond, then_branch, else_branch }) => {
+                if self.evaluate_recording(cond, n, known, lookups)? != 0 {
+                    self.evaluate_block(then_branch, n, known, lookups)
+                } else {
+                    self.evaluate_recording(&else_branch.as_ref()?.1, n, known, lookups)
+                }
+            }
+            Expr::Block(b) => self.evaluate_block(&b.block, n, known, lookups),
+            _ => None
+        }
+    }
+
+    /// only blocks consisting of a single expression can be evaluated
+    fn evaluate_block(&self, b: &Block, n: i128, known: &BTreeMap<i128, i128>, lookups: &mut Vec<i128>) -> Option<i128> {
+        match b.stmts.as_slice() {
+            [Stmt::Expr(e)] => self.evaluate_recording(e, n, known, lookups),
+            _ => None
+        }
+    }
+
+    /// value range of the type of expr if it can be derived from casts and recursive calls
+    fn type_bounds(&self, expr: &Expr) -> Option<(i128, i128)> {
+        match expr {
+            Expr::Cast(ExprCast{ attrs: _, expr: _, as_token: _, ty }) => integer_type_range(ty),
+            Expr::Paren(ExprParen{ attrs: _, paren_token: _, expr }) => self.type_bounds(expr),
+            Expr::Unary(ExprUnary{ attrs: _, op: UnOp::Neg(_), expr }) => self.type_bounds(expr),
+            Expr::Binary(ExprBinary{ attrs: _, left, op: BinOp::Add(_) | BinOp::Sub(_) | BinOp::Mul(_) | BinOp::Div(_) | BinOp::Rem(_), right }) => {
+                self.type_bounds(left).or_else(|| self.type_bounds(right))
+            }
+            Expr::Call(e) if try_get_ident(&e.func).is_some_and(|name| name == self.name) => self.bounds,
+            _ => None
+        }
+    }
+
+    fn check_bounds(&self, value: i128) -> Option<i128> {
+        match self.expression_bounds {
+            Some((min, max)) if value < min || value > max => None,
+            _ => Some(value)
+        }
+    }
+
+    fn evaluate_call(&self, e: &ExprCall, n: i128, known: &BTreeMap<i128, i128>, lookups: &mut Vec<i128>) -> Option<i128> {
+        if try_get_ident(&e.func)? != self.name || e.args.len() != 1 {
+            return None;
+        }
+        // the argument has the parameter type, not the type of the surrounding expression
+        let inner = Self { expression_bounds: None, ..*self };
+        let argument = inner.evaluate_recording(e.args.first()?, n, known, lookups)?;
+        lookups.push(argument);
+        known.get(&argument).copied()
+    }
+}
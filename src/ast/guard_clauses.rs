@@ -0,0 +1,183 @@
+use quote::quote;
+use syn::{BinOp, Block, Expr, ExprMethodCall, FnArg, ItemFn, LitInt, Pat, Stmt, Type, parse_quote};
+use syn::fold::Fold;
+use crate::ast::abstract_recursive_match::parse_non_typed_int;
+use crate::ast::constant_folding::Substitution;
+use crate::ast::optimizable_function::{integer_type_range, try_get_ident, try_get_int_lit};
+
+/// guards n < c and n <= c are expanded to at most this many constants
+const MAX_RANGE_CONSTANTS: i128 = 64;
+
+/// base cases written as guard clauses or an if-chain in front of the recursive formula
+///
+/// example: if n < 2 { return n; } fib(n - 1) + fib(n - 2) is read like
+/// match n { 0 => 0, 1 => 1, _ => fib(n - 1) + fib(n - 2) }
+pub struct GuardedBody {
+    /// the expression the conditions compare (n or s.len())
+    pub subject: Expr,
+    /// (argument, value) in the order of the conditions, a later guard of the same argument is unreachable
+    pub constants: Vec<(LitInt, Expr)>,
+    pub formula: Expr,
+}
+
+impl GuardedBody {
+    /// reads guard clauses if n == c { return v; } and an if-chain if n == c { v } else .. on the same
+    /// subject, None if the statements have another form
+    ///
+    /// the statements after the leading guards are the formula, so other early returns stay in it
+    pub fn read(f: &ItemFn, stmts: &[Stmt]) -> Option<Self> {
+        let mut reader = ConditionReader { f, subject: None };
+        let mut constants: Vec<(i128, &Expr)> = vec![];
+        let mut guards = 0;
+        for stmt in stmts {
+            let e = match stmt {
+                Stmt::Expr(Expr::If(e)) | Stmt::Semi(Expr::If(e), _) if e.else_branch.is_none() => e,
+                _ => break
+            };
+            let value = match e.then_branch.stmts.as_slice() {
+                [Stmt::Expr(Expr::Return(r))] | [Stmt::Semi(Expr::Return(r), _)] => r.expr.as_deref(),
+                _ => None
+            };
+            match (value, reader.arguments(&e.cond)) {
+                (Some(value), Some(arguments)) => add_constants(&mut constants, arguments, value),
+                _ => break
+            }
+            guards += 1;
+        }
+        let formula = match &stmts[guards..] {
+            [Stmt::Expr(e)] => {
+                let mut formula = e;
+                while let Expr::If(e) = formula {
+                    let arguments = match reader.arguments(&e.cond) {
+                        Some(arguments) => arguments,
+                        // an if on something else is part of the formula
+                        None => break
+                    };
+                    add_constants(&mut constants, arguments, branch_value(&e.then_branch)?);
+                    formula = match &e.else_branch {
+                        Some((_, else_branch)) => else_branch,
+                        None => return None
+                    };
+                }
+                match formula {
+                    Expr::Block(b) if b.label.is_none() => match b.block.stmts.as_slice() {
+                        [Stmt::Expr(e)] => e.clone(),
+                        _ => formula.clone()
+                    },
+                    _ => formula.clone()
+                }
+            }
+            [] => return None,
+            rest => parse_quote!({ #(#rest)* })
+        };
+        if constants.is_empty() {
+            return None;
+        }
+        let subject = reader.subject?;
+        let parameter = try_get_ident(&subject);
+        let constants = constants.into_iter().map(|(argument, value)| {
+            let argument = parse_non_typed_int(&argument);
+            // return n answers the argument of the constant itself
+            let value = match &parameter {
+                Some(parameter) => Substitution::new(parameter, &parse_quote!(#argument)).fold_expr(value.clone()),
+                None => value.clone()
+            };
+            (argument, value)
+        }).collect();
+        Some(Self { subject, constants, formula })
+    }
+}
+
+/// the first condition of an argument is the one which answers it
+fn add_constants<'e>(constants: &mut Vec<(i128, &'e Expr)>, arguments: Vec<i128>, value: &'e Expr) {
+    for argument in arguments {
+        if constants.iter().all(|(c, _)| *c != argument) {
+            constants.push((argument, value));
+        }
+    }
+}
+
+/// the value of a branch { v } or { return v; }
+fn branch_value(block: &Block) -> Option<&Expr> {
+    match block.stmts.as_slice() {
+        [Stmt::Expr(Expr::Return(r))] | [Stmt::Semi(Expr::Return(r), _)] => r.expr.as_deref(),
+        [Stmt::Expr(value)] => Some(value),
+        _ => None
+    }
+}
+
+/// reads the arguments a condition holds for, every condition has to compare the same subject
+struct ConditionReader<'a> {
+    f: &'a ItemFn,
+    subject: Option<Expr>,
+}
+
+impl<'a> ConditionReader<'a> {
+    /// the arguments of n == c, n < c, n <= c and their disjunction, None for other conditions
+    fn arguments(&mut self, cond: &Expr) -> Option<Vec<i128>> {
+        let e = match cond {
+            Expr::Paren(p) => return self.arguments(&p.expr),
+            Expr::Binary(e) => e,
+            _ => return None
+        };
+        if let BinOp::Or(_) = e.op {
+            let mut arguments = self.arguments(&e.left)?;
+            arguments.append(&mut self.arguments(&e.right)?);
+            return Some(arguments);
+        }
+        // c > n is read as n < c
+        let (side, constant, op) = match (try_get_int_lit(&e.left), try_get_int_lit(&e.right)) {
+            (_, Some(c)) => (&*e.left, c, e.op),
+            (Some(c), None) => (&*e.right, c, match e.op {
+                BinOp::Gt(_) => parse_quote!(<),
+                BinOp::Ge(_) => parse_quote!(<=),
+                op => op
+            }),
+            (None, None) => return None
+        };
+        let minimum = self.subject_minimum(side)?;
+        let constant: i128 = constant.base10_parse().ok()?;
+        let end = match op {
+            BinOp::Eq(_) => return Some(vec![constant]),
+            BinOp::Lt(_) => constant,
+            BinOp::Le(_) => constant + 1,
+            _ => return None
+        };
+        // the constants of the match can not be negative
+        if minimum < 0 || end - minimum > MAX_RANGE_CONSTANTS {
+            return None;
+        }
+        Some((minimum..end).collect())
+    }
+
+    /// the smallest value of a parameter n or of s.len(), None if the expression is no subject
+    /// or another subject was compared before
+    fn subject_minimum(&mut self, e: &Expr) -> Option<i128> {
+        let (parameter, is_length) = match e {
+            Expr::MethodCall(ExprMethodCall{ receiver, method, args, .. }) if method == "len" && args.is_empty() => (try_get_ident(receiver)?, true),
+            Expr::Path(p) if p.path.get_ident().is_some() => (try_get_ident(e)?, false),
+            _ => return None
+        };
+        let parameter_type = self.parameter_type(&parameter)?;
+        match &self.subject {
+            Some(subject) if quote!(#subject).to_string() != quote!(#e).to_string() => return None,
+            Some(_) => {}
+            None => self.subject = Some(e.clone())
+        }
+        if is_length {
+            Some(0)
+        } else {
+            Some(integer_type_range(parameter_type)?.0)
+        }
+    }
+
+    fn parameter_type(&self, parameter: &str) -> Option<&'a Type> {
+        self.f.sig.inputs.iter().find_map(|arg| match arg {
+            FnArg::Typed(t) => match &*t.pat {
+                Pat::Ident(i) if i.ident == parameter => Some(&*t.ty),
+                _ => None
+            },
+            FnArg::Receiver(_) => None
+        })
+    }
+}
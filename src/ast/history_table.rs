@@ -0,0 +1,109 @@
+use proc_macro_error::abort;
+use quote::quote;
+use syn::{Block, Expr, ExprCall, Ident, LitInt, Pat, Stmt, Type, parse_quote};
+use syn::fold::Fold;
+use crate::ast::abstract_recursive_match::parse_non_typed_int;
+use crate::ast::optimizable_function::OptimizableRecursiveFunction;
+use crate::ast::recursive_calls::CallReplacer;
+use crate::ast::termination::below_start_check;
+
+/// evaluates the function bottom-up into a table holding every value from the smallest constant up to n
+///
+/// used for recursive calls whose arguments are not a fixed step away from n and can reach all
+/// smaller arguments, e.g. catalan(n) = (0..n).map(|i| catalan(i) * catalan(n - 1 - i)).sum()
+pub struct HistoryTableFunction {
+    name: String,
+    parameter: Ident,
+    arg_type: Box<Type>,
+    return_type: Box<Type>,
+    constants: Vec<(LitInt, Expr)>,
+    formula: Expr,
+    /// smallest constant, the first entry of the table
+    start: i128,
+}
+
+impl HistoryTableFunction {
+    pub fn create_from(f: &OptimizableRecursiveFunction) -> Self {
+        if f.input_args().len() != 1 {
+            abort!(f.input_args()[0], "function need exactly 1 input argument!");
+        }
+        let parameter = match &*f.input_args()[0].pat {
+            Pat::Ident(i) => i.ident.clone(),
+            p => abort!(p, "must be an identifier")
+        };
+        let start = match f.constants().iter().filter_map(|(c, _)| c.base10_parse::<i128>().ok()).min() {
+            Some(start) => start,
+            None => abort!(f.recursive_formula(), "a table of all values needs at least 1 constant to start with")
+        };
+        Self {
+            name: f.name().to_string(),
+            parameter,
+            arg_type: f.input_args()[0].ty.clone(),
+            return_type: Box::new(f.return_type().clone()),
+            constants: f.constants().clone(),
+            formula: f.recursive_formula().clone(),
+            start,
+        }
+    }
+
+    /// convert the function body to statements filling the table up to the argument
+    pub fn construct_stmts(&self) -> Vec<Stmt> {
+        let parameter = &self.parameter;
+        let arg_type = &self.arg_type;
+        let return_type = &self.return_type;
+        let start = parse_non_typed_int(&self.start);
+        let arms = self.constants.iter().map(|(c, v)| quote!(#c => #v,));
+        let formula = self.replace_recursive_calls();
+        let below_start = below_start_check(parameter, arg_type, self.start);
+        let block: Block = parse_quote! {
+            {
+                #below_start
+                // early returns of the formula only leave the evaluation of one argument
+                let evaluate = |#parameter: #arg_type, table: &[#return_type]| -> #return_type {
+                    match #parameter {
+                        #(#arms)*
+                        _ => #formula
+                    }
+                };
+                let mut table: Vec<#return_type> = Vec::with_capacity((#parameter - #start) as usize + 1);
+                let mut argument: #arg_type = #start;
+                loop {
+                    let value = evaluate(argument, &table);
+                    if argument == #parameter {
+                        return value;
+                    }
+                    table.push(value);
+                    argument += 1;
+                }
+            }
+        };
+        block.stmts
+    }
+
+    /// rewrite the formula: recursive calls read the table
+    ///
+    /// the arguments can depend on computed values (q(n - q(n - 1))), so an assertion checks
+    /// that every argument is smaller than the current one and therefore already in the table
+    fn replace_recursive_calls(&self) -> Expr {
+        let start = parse_non_typed_int(&self.start);
+        let parameter = &self.parameter;
+        let message = format!("{}({{}}) needs {}({{}}) which is not computed yet", self.name, self.name);
+        let mut replacer = CallReplacer::new(&self.name, |call: ExprCall| {
+            if call.args.len() != 1 {
+                abort!(call, "recursive needs one argument");
+            }
+            let argument = &call.args[0];
+            let index: Expr = if self.start == 0 {
+                parse_quote!(table_argument as usize)
+            } else {
+                parse_quote!((table_argument - #start) as usize)
+            };
+            parse_quote!(table[{
+                let table_argument = #argument;
+                assert!(table_argument < #parameter, #message, #parameter, table_argument);
+                #index
+            }].clone())
+        });
+        replacer.fold_expr(self.formula.clone())
+    }
+}
@@ -0,0 +1,355 @@
+use proc_macro_error::abort;
+use std::collections::{BTreeMap, BTreeSet};
+use syn::{BinOp, Block, Expr, ExprCall, ExprRange, FnArg, Ident, ItemFn, Pat, PatIdent, RangeLimits, ReturnType, Stmt, Type, parse_quote};
+use syn::fold::Fold;
+use syn::visit::{self, Visit};
+use crate::ast::optimizable_function::{check_receiver, integer_type_range, try_get_int_lit};
+use crate::ast::recursive_calls::{collect_calls_in_expr, CallReplacer};
+
+/// a function of an interval i..=j whose recursive calls get smaller intervals inside it
+/// (matrix chain multiplication, optimal binary search trees, palindrome partitioning)
+///
+/// the body is evaluated for all intervals inside i..=j ordered by their length and the values are
+/// kept in a triangular table. other arguments have to be passed on unchanged
+///
+/// example: cost(i, j) = (i..j).map(|k| cost(i, k) + cost(k + 1, j) + split(i, k, j)).min().unwrap()
+pub struct IntervalFunction {
+    name: String,
+    /// the parameters i and j of the interval
+    bounds: (Ident, Ident),
+    /// position of i and j in the parameters
+    bound_positions: (usize, usize),
+    bound_type: Box<Type>,
+    return_type: Box<Type>,
+    body: Block,
+}
+
+impl IntervalFunction {
+    /// true if two integer parameters i and j change in the recursive calls and every call gets
+    /// an interval which provably lies inside i..=j and is smaller: its start is i + c (c >= 0) or a
+    /// loop variable of a range inside i..=j, its end is j - c or such a loop variable
+    ///
+    /// f(i, k) and f(k + 1, j) for k in i..j fit, binom(n - 1, k - 1) does not (the start moves down)
+    pub fn is_applicable(f: &ItemFn) -> bool {
+        let parameters = match typed_parameters(f) {
+            Some(parameters) => parameters,
+            None => return false
+        };
+        let block = &f.block;
+        let calls = collect_calls_in_expr(&parse_quote!(#block), &f.sig.ident.to_string());
+        if calls.is_empty() || calls.iter().any(|call| call.args.len() != parameters.len()) {
+            return false;
+        }
+        let changing: Vec<usize> = (0..parameters.len())
+            .filter(|&p| calls.iter().any(|call| !is_identifier(&call.args[p], &parameters[p].0)))
+            .collect();
+        let (first, second) = match changing.as_slice() {
+            [first, second] => (*first, *second),
+            _ => return false
+        };
+        let integers = integer_type_range(&parameters[first].1).is_some() && parameters[first].1 == parameters[second].1;
+        if !integers {
+            return false;
+        }
+        let bounds = match IntervalBounds::read(block, &parameters[first].0, &parameters[second].0) {
+            Some(bounds) => bounds,
+            None => return false
+        };
+        calls.iter().all(|call| bounds.is_smaller_interval(&call.args[first], &call.args[second]))
+    }
+
+    pub fn create_from(f: &ItemFn) -> Self {
+        for arg in &f.sig.inputs {
+            if let FnArg::Receiver(r) = arg {
+                check_receiver(r);
+            }
+        }
+        let name = f.sig.ident.to_string();
+        let parameters = typed_parameters(f).unwrap_or_else(|| abort!(f.sig, "all parameters must be identifiers"));
+        let return_type = match &f.sig.output {
+            ReturnType::Default => abort!(f.sig, "macro optimize_recursion needs a return value"),
+            ReturnType::Type(_, t) => t.clone()
+        };
+        let block = &f.block;
+        let calls = collect_calls_in_expr(&parse_quote!(#block), &name);
+        let changing: Vec<usize> = (0..parameters.len())
+            .filter(|&p| calls.iter().any(|call| !is_identifier(&call.args[p], &parameters[p].0)))
+            .collect();
+        let (first, second) = (changing[0], changing[1]);
+        Self {
+            name,
+            bounds: (parameters[first].0.clone(), parameters[second].0.clone()),
+            bound_positions: (first, second),
+            bound_type: Box::new(parameters[first].1.clone()),
+            return_type,
+            body: (*f.block).clone(),
+        }
+    }
+
+    /// convert the function body to statements filling the table by increasing interval length
+    pub fn construct_stmts(&self) -> Vec<Stmt> {
+        let (i, j) = &self.bounds;
+        let bound_type = &self.bound_type;
+        let return_type = &self.return_type;
+        let body = self.replace_recursive_calls();
+        let empty_body = self.replace_calls_of_empty_intervals();
+        let block: Block = parse_quote! {
+            {
+                let (interval_start, interval_end) = (#i, #j);
+                // empty intervals are not in the table, they are evaluated when they are needed
+                // (without calls, so variables which are only passed to calls are unused)
+                #[allow(unused_variables)]
+                let evaluate_empty = |#i: #bound_type, #j: #bound_type| -> #return_type #empty_body;
+                if #j < #i {
+                    return evaluate_empty(#i, #j);
+                }
+                let interval_positions = (#j - #i) as usize + 1;
+                // early returns of the body only leave the evaluation of one interval
+                let evaluate = |#i: #bound_type, #j: #bound_type, table: &[#return_type]| -> #return_type #body;
+                let mut table: Vec<#return_type> = Vec::with_capacity(interval_positions * (interval_positions + 1) / 2);
+                for length in 0..interval_positions {
+                    for offset in 0..interval_positions - length {
+                        let start = interval_start + offset as #bound_type;
+                        let value = evaluate(start, start + length as #bound_type, &table);
+                        table.push(value);
+                    }
+                }
+                table[table.len() - 1].clone()
+            }
+        };
+        block.stmts
+    }
+
+    /// rewrite the body: calls of empty intervals are evaluated directly, the others read the table
+    ///
+    /// rows of the table hold the intervals of the same length, a debug assertion checks that
+    /// a called interval lies inside the argument and is shorter than the current one
+    fn replace_recursive_calls(&self) -> Block {
+        let (i, j) = &self.bounds;
+        let bound_type = &self.bound_type;
+        let message = format!("{}({{}}, {{}}) needs {}({{}}, {{}}) which is not computed yet", self.name, self.name);
+        let mut replacer = CallReplacer::new(&self.name, |call: ExprCall| {
+            let (start, end) = (&call.args[self.bound_positions.0], &call.args[self.bound_positions.1]);
+            // parenthesized, a leading block would end a match arm when the tokens are parsed again
+            parse_quote!(({
+                let interval: (#bound_type, #bound_type) = (#start, #end);
+                if interval.1 < interval.0 {
+                    evaluate_empty(interval.0, interval.1)
+                } else {
+                    debug_assert!(interval_start <= interval.0 && interval.1 <= interval_end && interval.1 - interval.0 < #j - #i,
+                        #message, #i, #j, interval.0, interval.1);
+                    let length = (interval.1 - interval.0) as usize;
+                    table[length * (2 * interval_positions - length + 1) / 2 + (interval.0 - interval_start) as usize].clone()
+                }
+            }))
+        });
+        replacer.fold_block(self.body.clone())
+    }
+
+    /// rewrite the body for an empty interval which has no smaller intervals to call
+    fn replace_calls_of_empty_intervals(&self) -> Block {
+        let (i, j) = &self.bounds;
+        let return_type = &self.return_type;
+        let message = format!("{}({{}}, {{}}) is an empty interval and can not call {}", self.name, self.name);
+        // a value of the return type which panics, a bare panic would make the surrounding code unreachable
+        let mut replacer = CallReplacer::new(&self.name, |_| parse_quote!(None::<#return_type>.unwrap_or_else(|| panic!(#message, #i, #j))));
+        replacer.fold_block(self.body.clone())
+    }
+}
+
+/// identifier and type of every parameter except self, None if a parameter is not an identifier
+fn typed_parameters(f: &ItemFn) -> Option<Vec<(Ident, Type)>> {
+    f.sig.inputs.iter().filter_map(|arg| match arg {
+        FnArg::Typed(t) => match &*t.pat {
+            Pat::Ident(i) => Some(Some((i.ident.clone(), (*t.ty).clone()))),
+            _ => Some(None)
+        },
+        FnArg::Receiver(_) => None
+    }).collect()
+}
+
+fn is_identifier(e: &Expr, identifier: &Ident) -> bool {
+    matches!(e, Expr::Path(p) if p.path.is_ident(identifier))
+}
+
+/// i + c or j + c, inside the evaluation of a non empty interval i <= j holds
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Bound {
+    Start(i128),
+    End(i128),
+}
+
+impl Bound {
+    fn shift(self, c: i128) -> Self {
+        match self {
+            Bound::Start(offset) => Bound::Start(offset + c),
+            Bound::End(offset) => Bound::End(offset + c)
+        }
+    }
+
+    /// true if the bound is at least i + c (j + c >= i + c)
+    fn at_least_start(self, c: i128) -> bool {
+        match self {
+            Bound::Start(offset) | Bound::End(offset) => offset >= c
+        }
+    }
+
+    /// true if the bound is at most j - c (i - c <= j - c)
+    fn at_most_end(self, c: i128) -> bool {
+        match self {
+            Bound::Start(offset) | Bound::End(offset) => offset <= -c
+        }
+    }
+}
+
+/// the lowest and highest value of the arguments of the recursive calls in terms of i and j
+struct IntervalBounds {
+    start: String,
+    end: String,
+    /// loop variables of a range whose bounds are known (for k in i..j, (i..j).map(|k| ..))
+    variables: BTreeMap<String, (Bound, Bound)>,
+}
+
+impl IntervalBounds {
+    /// None if i or j are bound again inside the body
+    fn read(block: &Block, start: &Ident, end: &Ident) -> Option<Self> {
+        let mut counter = BindingCounter::default();
+        counter.visit_block(block);
+        if counter.bindings.contains_key(&start.to_string()) || counter.bindings.contains_key(&end.to_string()) {
+            return None;
+        }
+        // a variable is only known if every binding of its name is a loop over a range
+        let loop_variables = counter.bindings.into_iter()
+            .filter(|(name, count)| counter.range_bindings.get(name) == Some(count))
+            .map(|(name, _)| name)
+            .collect();
+        let mut collector = RangeCollector {
+            bounds: Self { start: start.to_string(), end: end.to_string(), variables: BTreeMap::new() },
+            loop_variables,
+        };
+        collector.visit_block(block);
+        Some(collector.bounds)
+    }
+
+    /// the lowest and highest value of an expression, None if it is not known
+    fn range(&self, e: &Expr) -> Option<(Bound, Bound)> {
+        match e {
+            Expr::Paren(p) => self.range(&p.expr),
+            Expr::Path(p) => {
+                let name = p.path.get_ident()?.to_string();
+                if name == self.start {
+                    Some((Bound::Start(0), Bound::Start(0)))
+                } else if name == self.end {
+                    Some((Bound::End(0), Bound::End(0)))
+                } else {
+                    self.variables.get(&name).copied()
+                }
+            }
+            Expr::Binary(b) => {
+                let constant = |e: &Expr| try_get_int_lit(e).and_then(|c| c.base10_parse::<i128>().ok());
+                let (range, c) = match (b.op, constant(&b.left), constant(&b.right)) {
+                    (BinOp::Add(_), _, Some(c)) => (self.range(&b.left)?, c),
+                    (BinOp::Add(_), Some(c), None) => (self.range(&b.right)?, c),
+                    (BinOp::Sub(_), _, Some(c)) => (self.range(&b.left)?, -c),
+                    _ => return None
+                };
+                Some((range.0.shift(c), range.1.shift(c)))
+            }
+            _ => None
+        }
+    }
+
+    /// true if start..=end lies inside i..=j and is smaller
+    fn is_smaller_interval(&self, start: &Expr, end: &Expr) -> bool {
+        match (self.range(start), self.range(end)) {
+            (Some((lowest_start, _)), Some((_, highest_end))) => lowest_start.at_least_start(0) && highest_end.at_most_end(0)
+                && (lowest_start.at_least_start(1) || highest_end.at_most_end(1)),
+            _ => false
+        }
+    }
+}
+
+/// the pattern bound to the elements of a range (for k in a..b, (a..b).map(|k| ..))
+fn range_binding<'a>(pat: &'a Pat, range: &'a Expr) -> Option<(&'a PatIdent, &'a ExprRange)> {
+    match (range, pat) {
+        (Expr::Paren(p), _) => range_binding(pat, &p.expr),
+        (Expr::Range(range), Pat::Ident(i)) => Some((i, range)),
+        _ => None
+    }
+}
+
+/// calls the function with the pattern and the range of every loop over a range
+fn visit_ranges<'ast>(e: &'ast Expr, mut add: impl FnMut(&'ast PatIdent, &'ast ExprRange)) {
+    match e {
+        Expr::ForLoop(f) => {
+            if let Some((pat, range)) = range_binding(&f.pat, &f.expr) {
+                add(pat, range);
+            }
+        }
+        Expr::MethodCall(m) => {
+            for arg in &m.args {
+                if let Expr::Closure(c) = arg {
+                    if let Some((pat, range)) = c.inputs.first().filter(|_| c.inputs.len() == 1).and_then(|pat| range_binding(pat, &m.receiver)) {
+                        add(pat, range);
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// counts how often every name is bound and how often it is bound by a loop over a range
+#[derive(Default)]
+struct BindingCounter {
+    bindings: BTreeMap<String, usize>,
+    range_bindings: BTreeMap<String, usize>,
+}
+
+impl<'ast> Visit<'ast> for BindingCounter {
+    fn visit_expr(&mut self, e: &'ast Expr) {
+        let range_bindings = &mut self.range_bindings;
+        visit_ranges(e, |pat, _| *range_bindings.entry(pat.ident.to_string()).or_insert(0) += 1);
+        visit::visit_expr(self, e);
+    }
+
+    fn visit_pat_ident(&mut self, p: &'ast PatIdent) {
+        *self.bindings.entry(p.ident.to_string()).or_insert(0) += 1;
+        visit::visit_pat_ident(self, p);
+    }
+}
+
+/// reads the bounds of the ranges of the loop variables, outer loops first
+struct RangeCollector {
+    bounds: IntervalBounds,
+    loop_variables: BTreeSet<String>,
+}
+
+impl<'ast> Visit<'ast> for RangeCollector {
+    fn visit_expr(&mut self, e: &'ast Expr) {
+        let mut found = vec![];
+        visit_ranges(e, |pat, range| found.push((pat.ident.to_string(), range)));
+        for (name, range) in found {
+            if !self.loop_variables.contains(&name) {
+                continue
+            }
+            let lowest = range.from.as_ref().and_then(|from| self.bounds.range(from)).map(|(lowest, _)| lowest);
+            let highest = range.to.as_ref().and_then(|to| self.bounds.range(to)).map(|(_, highest)| match range.limits {
+                RangeLimits::HalfOpen(_) => highest.shift(-1),
+                RangeLimits::Closed(_) => highest
+            });
+            match (lowest.zip(highest), self.bounds.variables.get(&name)) {
+                (Some(bounds), None) => {
+                    self.bounds.variables.insert(name, bounds);
+                }
+                (Some(bounds), Some(known)) if bounds == *known => {}
+                // the name is bound to ranges with other bounds
+                _ => {
+                    self.bounds.variables.remove(&name);
+                    self.loop_variables.remove(&name);
+                }
+            }
+        }
+        visit::visit_expr(self, e);
+    }
+}
@@ -0,0 +1,71 @@
+use std::collections::BTreeMap;
+use syn::{BinOp, Expr, ExprBinary, ExprCall, ExprParen, ExprUnary, UnOp};
+use crate::ast::optimizable_function::try_get_int_lit;
+
+/// a recursive formula of the form c + a_1 * f(n - k_1) + ... + a_m * f(n - k_m)
+/// with integer coefficients a_i
+#[derive(Debug, Clone, Default)]
+pub struct LinearFormula {
+    /// steps k_i of the recursive calls mapped to their coefficient a_i
+    pub coefficients: BTreeMap<u128, i128>,
+    pub constant: i128,
+}
+
+impl LinearFormula {
+    /// read the linear form of expr, recursive_steps returns the steps of a recursive call
+    /// (None for any other call)
+    ///
+    /// returns None if expr is not linear in the recursive calls
+    pub fn from_expr(expr: &Expr, recursive_steps: &dyn Fn(&ExprCall) -> Option<u128>) -> Option<Self> {
+        match expr {
+            Expr::Lit(_) => Some(Self { coefficients: BTreeMap::new(), constant: try_get_int_lit(expr)?.base10_parse().ok()? }),
+            Expr::Call(e) => {
+                let steps = recursive_steps(e)?;
+                let mut coefficients = BTreeMap::new();
+                coefficients.insert(steps, 1);
+                Some(Self { coefficients, constant: 0 })
+            }
+            Expr::Paren(ExprParen{ attrs: _, paren_token: _, expr }) => Self::from_expr(expr, recursive_steps),
+            Expr::Unary(ExprUnary{ attrs: _, op: UnOp::Neg(_), expr }) => Self::from_expr(expr, recursive_steps)?.scale(-1),
+            Expr::Binary(ExprBinary{ attrs: _, left, op, right }) => {
+                let left = Self::from_expr(left, recursive_steps)?;
+                let right = Self::from_expr(right, recursive_steps)?;
+                match op {
+                    BinOp::Add(_) => left.add(&right, 1),
+                    BinOp::Sub(_) => left.add(&right, -1),
+                    BinOp::Mul(_) if left.coefficients.is_empty() => right.scale(left.constant),
+                    BinOp::Mul(_) if right.coefficients.is_empty() => left.scale(right.constant),
+                    _ => None
+                }
+            }
+            _ => None
+        }
+    }
+
+    /// steps of the recursive call which is farthest away from n
+    pub fn farthest_step(&self) -> Option<u128> {
+        self.coefficients.keys().next_back().copied()
+    }
+
+    fn scale(mut self, factor: i128) -> Option<Self> {
+        for a in self.coefficients.values_mut() {
+            *a = a.checked_mul(factor)?;
+        }
+        self.constant = self.constant.checked_mul(factor)?;
+        Some(self)
+    }
+
+    /// self + sign * other
+    fn add(mut self, other: &Self, sign: i128) -> Option<Self> {
+        for (k, a) in &other.coefficients {
+            let sum = self.coefficients.get(k).copied().unwrap_or(0).checked_add(a.checked_mul(sign)?)?;
+            if sum == 0 {
+                self.coefficients.remove(k);
+            } else {
+                self.coefficients.insert(*k, sum);
+            }
+        }
+        self.constant = self.constant.checked_add(other.constant.checked_mul(sign)?)?;
+        Some(self)
+    }
+}
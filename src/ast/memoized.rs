@@ -0,0 +1,315 @@
+use proc_macro_error::abort;
+use quote::quote;
+use syn::{BinOp, Block, Expr, ExprCall, ExprClosure, ExprReturn, FnArg, ItemFn, Lifetime, Pat, PatType, ReturnType, Stmt, Type, parse_quote};
+use syn::fold::{self, Fold};
+use crate::ast::abstract_recursive_match::parse_non_typed_int;
+use crate::ast::optimizable_function::{OptimizableRecursiveFunction, check_receiver, integer_type_range, try_get_ident, try_get_int_lit};
+use crate::ast::recursive_calls::{collect_calls_in_expr, CallReplacer};
+
+/// container for the already computed function values in the generated code
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum MemoKind {
+    /// Vec of (argument, value) sorted by argument, for small sets of arguments
+    Sorted,
+    /// Vec indexed by the argument for the arguments below DENSE_LIMIT and a HashMap for the others,
+    /// for recursions which only step down from an unsigned argument
+    Dense,
+    /// HashMap from argument to value
+    Hash,
+}
+
+/// largest number of entries allocated up front by a dense memo, larger arguments use its HashMap
+const DENSE_LIMIT: i128 = 1 << 20;
+
+impl MemoKind {
+    /// dense if every recursive call is f(n - k) with an unsigned parameter n
+    /// (all needed arguments lie in 0..=n), a hash map otherwise
+    pub fn choose(f: &OptimizableRecursiveFunction) -> Self {
+        if f.input_args().len() != 1 || !matches!(integer_type_range(&f.input_args()[0].ty), Some((0, _))) {
+            return MemoKind::Hash;
+        }
+        let parameter = match &*f.input_args()[0].pat {
+            Pat::Ident(i) => i.ident.to_string(),
+            _ => return MemoKind::Hash
+        };
+        let steps_down = collect_calls_in_expr(f.recursive_formula(), f.name()).iter()
+            .flat_map(|call| call.args.iter())
+            .all(|arg| matches!(arg, Expr::Binary(e) if matches!(e.op, BinOp::Sub(_))
+                && try_get_ident(&e.left).as_deref() == Some(parameter.as_str())
+                && try_get_int_lit(&e.right).is_some()));
+        if steps_down {
+            MemoKind::Dense
+        } else {
+            MemoKind::Hash
+        }
+    }
+}
+
+/// evaluates the function with a memo and an explicit stack of pending arguments instead of recursion
+///
+/// every argument on the stack is evaluated with the original match, a recursive call whose value is
+/// not in the memo yet aborts the evaluation and pushes its argument, the evaluation is repeated once
+/// the missing value is known
+pub struct MemoizedFunction {
+    name: String,
+    input_args: Vec<PatType>,
+    return_type: Box<Type>,
+    body: Expr,
+    kind: MemoKind,
+}
+
+impl MemoizedFunction {
+    pub fn create_from(f: &OptimizableRecursiveFunction, kind: MemoKind) -> Self {
+        let matched = f.matched_expr();
+        let arms = f.constants().iter().map(|(c, v)| quote!(#c => #v,));
+        let formula = f.recursive_formula();
+        let body = parse_quote! {
+            match #matched {
+                #(#arms)*
+                _ => #formula
+            }
+        };
+        Self {
+            name: f.name().to_string(),
+            input_args: f.input_args().clone(),
+            return_type: Box::new(f.return_type().clone()),
+            body,
+            kind,
+        }
+    }
+
+    /// memoize the whole body of a function, used for nested calls like f(f(n + 11))
+    /// whose arguments are only known after the inner call is evaluated
+    pub fn from_body(f: &ItemFn, kind: MemoKind) -> Self {
+        let input_args: Vec<PatType> = f.sig.inputs.iter().filter_map(|arg| match arg {
+            FnArg::Receiver(r) => {
+                check_receiver(r);
+                None
+            }
+            FnArg::Typed(t) => Some(t.clone())
+        }).collect();
+        if input_args.is_empty() {
+            abort!(f.sig, "function need at least 1 argument because we assume no side effects");
+        }
+        let return_type = match &f.sig.output {
+            ReturnType::Default => abort!(f.sig, "macro optimize_recursion needs a return value"),
+            ReturnType::Type(_, t) => t.clone()
+        };
+        let block = &f.block;
+        Self {
+            name: f.sig.ident.to_string(),
+            input_args,
+            return_type,
+            body: parse_quote!(#block),
+            kind,
+        }
+    }
+
+    /// convert the function body to statements evaluating it with a memo
+    pub fn construct_stmts(&self) -> Vec<Stmt> {
+        let key_type = key_type(&self.input_args);
+        let key_pattern = key_pattern(&self.input_args);
+        let key = key_expr(&self.input_args);
+        let return_type = &self.return_type;
+        let memo_type = self.memo_type();
+        let memo_new = self.memo_new();
+        let lookup_key = self.lookup(&parse_quote!(key));
+        let insert = self.insert();
+        let body = self.replace_recursive_calls();
+        let block: Block = parse_quote! {
+            {
+                let evaluate = |#key_pattern: #key_type, memo: &#memo_type| -> Result<#return_type, #key_type> {
+                    Ok(#body)
+                };
+                let key: #key_type = #key;
+                let mut memo: #memo_type = #memo_new;
+                let mut stack: Vec<#key_type> = vec![key];
+                // arguments on the stack, an argument waiting for itself never terminates
+                let mut pending: std::collections::BTreeSet<#key_type> = std::collections::BTreeSet::from([key]);
+                while let Some(&argument) = stack.last() {
+                    match evaluate(argument, &memo) {
+                        Ok(value) => {
+                            stack.pop();
+                            pending.remove(&argument);
+                            #insert
+                        }
+                        Err(missing) => {
+                            if !pending.insert(missing) {
+                                panic!("recursion does not terminate for argument {:?}", missing);
+                            }
+                            stack.push(missing);
+                        }
+                    }
+                }
+                #lookup_key.expect("value is computed")
+            }
+        };
+        block.stmts
+    }
+
+    /// rewrite the body: recursive calls read from the memo or return the missing argument
+    fn replace_recursive_calls(&self) -> Expr {
+        for call in collect_calls_in_expr(&self.body, &self.name) {
+            if call.args.len() != self.input_args.len() {
+                abort!(call, "recursive call needs {} arguments", self.input_args.len());
+            }
+        }
+        let key_type = key_type(&self.input_args);
+        let lookup = self.lookup(&parse_quote!(argument));
+        let mut replacer = CallReplacer::new(&self.name, |call: ExprCall| {
+            let args = call.args.iter();
+            let argument: Expr = if self.input_args.len() == 1 {
+                parse_quote!(#(#args)*)
+            } else {
+                parse_quote!((#(#args),*))
+            };
+            // parenthesized, a leading block would end a match arm when the tokens are parsed again
+            parse_quote!(({
+                let argument: #key_type = #argument;
+                match #lookup {
+                    Some(value) => value,
+                    None => return Err(argument)
+                }
+            }))
+        });
+        let body = ReturnWrapper::new(&self.name).fold_expr(self.body.clone());
+        replacer.fold_expr(body)
+    }
+
+    fn memo_type(&self) -> Type {
+        let key_type = key_type(&self.input_args);
+        let return_type = &self.return_type;
+        match self.kind {
+            MemoKind::Sorted => parse_quote!(Vec<(#key_type, #return_type)>),
+            MemoKind::Dense => parse_quote!((Vec<Option<#return_type>>, std::collections::HashMap<#key_type, #return_type>)),
+            MemoKind::Hash => parse_quote!(std::collections::HashMap<#key_type, #return_type>),
+        }
+    }
+
+    fn memo_new(&self) -> Expr {
+        let limit = parse_non_typed_int(&DENSE_LIMIT);
+        match self.kind {
+            MemoKind::Sorted => parse_quote!(Vec::new()),
+            MemoKind::Dense => parse_quote!((
+                vec![None; (key as u128).saturating_add(1).min(#limit) as usize],
+                std::collections::HashMap::new()
+            )),
+            MemoKind::Hash => parse_quote!(std::collections::HashMap::new()),
+        }
+    }
+
+    /// expression of type Option<return type> reading the value for key from the memo
+    fn lookup(&self, key: &Expr) -> Expr {
+        match self.kind {
+            MemoKind::Sorted => parse_quote!(memo.binary_search_by_key(&#key, |e| e.0).ok().map(|i| memo[i].1.clone())),
+            MemoKind::Dense => parse_quote!(if (#key as u128) < memo.0.len() as u128 {
+                memo.0[#key as usize].clone()
+            } else {
+                memo.1.get(&#key).cloned()
+            }),
+            MemoKind::Hash => parse_quote!(memo.get(&#key).cloned()),
+        }
+    }
+
+    /// statement storing value for argument in the memo
+    fn insert(&self) -> Stmt {
+        match self.kind {
+            MemoKind::Sorted => parse_quote! {
+                if let Err(i) = memo.binary_search_by_key(&argument, |e| e.0) {
+                    memo.insert(i, (argument, value));
+                }
+            },
+            MemoKind::Dense => parse_quote! {
+                if (argument as u128) < memo.0.len() as u128 {
+                    memo.0[argument as usize] = Some(value);
+                } else {
+                    memo.1.insert(argument, value);
+                }
+            },
+            MemoKind::Hash => parse_quote!(memo.insert(argument, value);),
+        }
+    }
+}
+
+/// type of the memo key: the parameter type or a tuple of all parameter types
+pub fn key_type(input_args: &[PatType]) -> Type {
+    let types = input_args.iter().map(|a| &a.ty);
+    if input_args.len() == 1 {
+        parse_quote!(#(#types)*)
+    } else {
+        parse_quote!((#(#types),*))
+    }
+}
+
+/// pattern binding the parameters from a key
+pub fn key_pattern(input_args: &[PatType]) -> Pat {
+    let patterns = input_args.iter().map(|a| &a.pat);
+    if input_args.len() == 1 {
+        parse_quote!(#(#patterns)*)
+    } else {
+        parse_quote!((#(#patterns),*))
+    }
+}
+
+/// the key of the function arguments
+pub fn key_expr(input_args: &[PatType]) -> Expr {
+    let idents = input_args.iter().map(|a| match &*a.pat {
+        Pat::Ident(i) => i.ident.clone(),
+        p => abort!(p, "must be an identifier")
+    });
+    if input_args.len() == 1 {
+        parse_quote!(#(#idents)*)
+    } else {
+        parse_quote!((#(#idents),*))
+    }
+}
+
+/// wraps the values of early returns into Ok and rejects recursive calls inside closures
+/// (their missing argument can not be returned from the evaluation)
+pub struct ReturnWrapper<'a> {
+    name: &'a str,
+    /// returns break out of the block with this label instead
+    label: Option<Lifetime>,
+}
+
+impl<'a> ReturnWrapper<'a> {
+    pub fn new(name: &'a str) -> Self {
+        Self { name, label: None }
+    }
+
+    /// turn early returns into breaks out of the labeled block the body is evaluated in
+    pub fn with_label(mut self, label: Lifetime) -> Self {
+        self.label = Some(label);
+        self
+    }
+}
+
+impl<'a> Fold for ReturnWrapper<'a> {
+    fn fold_expr(&mut self, e: Expr) -> Expr {
+        match (e, &self.label) {
+            (Expr::Return(r), Some(label)) => {
+                let label = label.clone();
+                match fold::fold_expr_return(self, r).expr {
+                    Some(value) => parse_quote!(break #label Ok(#value)),
+                    None => parse_quote!(break #label Ok(()))
+                }
+            }
+            (e, _) => fold::fold_expr(self, e)
+        }
+    }
+
+    fn fold_expr_return(&mut self, e: ExprReturn) -> ExprReturn {
+        let mut e = fold::fold_expr_return(self, e);
+        if let Some(value) = e.expr.take() {
+            e.expr = Some(Box::new(parse_quote!(Ok(#value))));
+        }
+        e
+    }
+
+    fn fold_expr_closure(&mut self, e: ExprClosure) -> ExprClosure {
+        if !collect_calls_in_expr(&e.body, self.name).is_empty() {
+            abort!(e, "recursive calls inside closures can not be evaluated without recursion");
+        }
+        e
+    }
+}
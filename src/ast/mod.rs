@@ -1,4 +1,21 @@
 mod optimizable_function;
 mod abstract_recursive_match;
+mod formula_evaluation;
+mod coverage;
+mod termination;
+mod linear_formula;
+mod recursive_calls;
+mod memoized;
+mod stack_machine;
+mod tail_recursion;
+mod mutual_recursion;
+mod history_table;
+mod constant_folding;
+mod guard_clauses;
+mod suffix_recursion;
+mod two_sequences;
+mod interval_recursion;
+mod finite_states;
 
-pub use optimizable_function::OptimizableFunction;
\ No newline at end of file
+pub use optimizable_function::OptimizableFunction;
+pub use mutual_recursion::MutualRecursion;
\ No newline at end of file
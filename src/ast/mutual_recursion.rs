@@ -0,0 +1,311 @@
+use std::collections::BTreeSet;
+use proc_macro_error::abort;
+use syn::{BinOp, Expr, ExprCall, ExprMatch, FnArg, Ident, Item, ItemConst, ItemFn, ItemMod, Pat, ReturnType, Stmt, Type, Visibility, parse_quote};
+use proc_macro2::Span;
+use syn::fold::{self, Fold};
+use syn::visit::Visit;
+use crate::ast::abstract_recursive_match::parse_non_typed_int;
+use crate::ast::constant_folding::{ConstInliner, IteratorUnroller};
+use crate::ast::optimizable_function::{try_get_ident, try_get_int_lit};
+use crate::ast::recursive_calls::{collect_calls_in_expr, try_call_name};
+use crate::ast::termination::below_start_check;
+
+/// functions of a module which call each other (mutual recursion)
+///
+/// all functions are computed together upwards from the smallest constant, a joint window holds
+/// the latest values of every function
+///
+/// example: a(n) = a(n - 1) + b(n - 1), b(n) = a(n - 1)
+pub struct MutualRecursion {
+    module: ItemMod,
+    functions: Vec<MutualFunction>,
+    /// the window holds the values of the last window_size arguments
+    window_size: u128,
+    /// smallest constant argument of all functions, the computation starts there
+    start: i128,
+}
+
+struct MutualFunction {
+    name: String,
+    parameter: Ident,
+    arg_type: Box<Type>,
+    return_type: Box<Type>,
+    constants: BTreeSet<i128>,
+    body: ExprMatch,
+    /// (index of the called function, steps) of every call of a group function in the formula
+    calls: Vec<(usize, u128)>,
+}
+
+impl MutualRecursion {
+    pub fn create_from(module: &ItemMod) -> Self {
+        let items = match &module.content {
+            Some((_, items)) => items,
+            None => abort!(module, "the module needs to be inline to optimize its functions")
+        };
+        let functions = recursive_functions(items);
+        if functions.is_empty() {
+            abort!(module, "the module has no recursive functions to optimize");
+        }
+        // integer consts of the module can be step sizes or offsets like consts in a function body
+        let const_items: Vec<ItemConst> = items.iter().filter_map(|item| match item {
+            Item::Const(c) => Some(c.clone()),
+            _ => None
+        }).collect();
+        let mut inliner = ConstInliner::for_module(&const_items);
+        let names: Vec<String> = functions.iter().map(|f| f.sig.ident.to_string()).collect();
+        let functions: Vec<MutualFunction> = functions.into_iter()
+            .map(|f| MutualFunction::create_from(f, &names, &mut inliner))
+            .collect();
+        if let Some(f) = functions.iter().find(|f| f.arg_type != functions[0].arg_type) {
+            abort!(f.arg_type, "all functions of the module need the same argument type");
+        }
+        let window_size = functions.iter()
+            .flat_map(|f| f.calls.iter().map(|(_, steps)| *steps))
+            .max()
+            .unwrap_or_else(|| abort!(module, "the functions of the module do not call each other"));
+        let start = match functions.iter().flat_map(|f| f.constants.iter()).min() {
+            Some(start) => *start,
+            None => abort!(module, "the functions of the module need at least 1 constant")
+        };
+        let result = Self { module: module.clone(), functions, window_size, start };
+        result.check_start();
+        result
+    }
+
+    /// every argument of the first window which is not a constant has to find its predecessors
+    /// inside the window (the computation can not start otherwise)
+    fn check_start(&self) {
+        for f in &self.functions {
+            for offset in 0..self.window_size {
+                let argument = self.start + offset as i128;
+                if f.constants.contains(&argument) {
+                    continue
+                }
+                if let Some((called, steps)) = f.calls.iter().find(|(_, steps)| *steps > offset) {
+                    abort!(f.body.expr, "{}({}) is not a constant and needs {}({}) which lies below the smallest constant {}",
+                        f.name, argument, self.functions[*called].name, argument - *steps as i128, self.start;
+                        help = "add a constant for {}({})", f.name, argument);
+                }
+            }
+        }
+    }
+
+    /// the module with the recursion of every function replaced by the joint window loop
+    pub fn optimize(&self) -> ItemMod {
+        let mut module = self.module.clone();
+        if let Some((_, items)) = &mut module.content {
+            for item in items.iter_mut() {
+                // other functions and items are kept as they are
+                if let Item::Fn(f) = item {
+                    let name = f.sig.ident.to_string();
+                    if let Some(index) = self.functions.iter().position(|g| g.name == name) {
+                        let block = self.construct_block(index);
+                        // the window index arithmetic can contain % 1 for a window of size 1
+                        *f.block = parse_quote!({
+                            #[allow(clippy::modulo_one)]
+                            #block
+                        });
+                    }
+                }
+            }
+            // the functions read inlined copies of the integer consts, private consts
+            // nothing else reads are dropped
+            let unused: Vec<usize> = (0..items.len()).filter(|i| match &items[*i] {
+                Item::Const(c) => ConstInliner::is_inlinable(c) && matches!(c.vis, Visibility::Inherited)
+                    && !items.iter().enumerate().any(|(j, other)| j != *i && mentions(other, &c.ident)),
+                _ => false
+            }).collect();
+            for i in unused.into_iter().rev() {
+                items.remove(i);
+            }
+        }
+        module
+    }
+
+    /// body of the function with the given index: compute all functions up to the argument
+    fn construct_block(&self, index: usize) -> syn::Block {
+        let f = &self.functions[index];
+        let parameter = &f.parameter;
+        let arg_type = &f.arg_type;
+        let start = parse_non_typed_int(&self.start);
+        let window_size = self.window_size as usize;
+        let windows: Vec<Ident> = self.functions.iter().map(|g| window_ident(&g.name)).collect();
+        let values: Vec<Ident> = self.functions.iter().map(|g| value_ident(&g.name)).collect();
+        let return_types = self.functions.iter().map(|g| &g.return_type);
+        let evaluations = self.functions.iter().map(|g| self.create_evaluation(g));
+        let value = &values[index];
+        let below_start = below_start_check(parameter, arg_type, self.start);
+        let return_types_again = self.functions.iter().map(|g| &g.return_type);
+        parse_quote! {
+            {
+                #below_start
+                #(let mut #windows: [Option<#return_types>; #window_size] = [None; #window_size];)*
+                let mut argument: #arg_type = #start;
+                let mut i: usize = 0;
+                loop {
+                    #(let #values: #return_types_again = #evaluations;)*
+                    #(#windows[i % #window_size] = Some(#values);)*
+                    if argument == #parameter {
+                        return #value;
+                    }
+                    argument += 1;
+                    i += 1;
+                }
+            }
+        }
+    }
+
+    /// the original match of g evaluated for argument, calls read from the windows
+    fn create_evaluation(&self, g: &MutualFunction) -> Expr {
+        let parameter = &g.parameter;
+        let mut replacer = WindowReplacer { recursion: self, parameter };
+        let body = replacer.fold_expr_match(g.body.clone());
+        parse_quote!({
+            let #parameter = argument;
+            #body
+        })
+    }
+}
+
+impl MutualFunction {
+    fn create_from(f: &ItemFn, names: &[String], inliner: &mut ConstInliner) -> Self {
+        let name = f.sig.ident.to_string();
+        if f.sig.inputs.len() != 1 {
+            abort!(f.sig, "mutual recursive functions need exactly 1 input argument");
+        }
+        let (parameter, arg_type) = match &f.sig.inputs[0] {
+            FnArg::Typed(t) => match &*t.pat {
+                Pat::Ident(i) => (i.ident.clone(), t.ty.clone()),
+                p => abort!(p, "must be an identifier")
+            },
+            FnArg::Receiver(_) => abort!(f.sig, "input function arg self is not allowed")
+        };
+        let return_type = match &f.sig.output {
+            ReturnType::Default => abort!(f.sig, "macro optimize_recursion needs a return value"),
+            ReturnType::Type(_, t) => t.clone()
+        };
+        let body = match f.block.stmts.as_slice() {
+            [Stmt::Expr(Expr::Match(m))] => IteratorUnroller.fold_expr_match(inliner.fold_expr_match(m.clone())),
+            _ => abort!(f.block, "function can only have one match statement")
+        };
+        let mut constants = BTreeSet::new();
+        let mut formula = None;
+        for arm in &body.arms {
+            match &arm.pat {
+                Pat::Lit(p) => {
+                    match try_get_int_lit(&p.expr).and_then(|c| c.base10_parse::<i128>().ok()) {
+                        Some(c) => constants.insert(c),
+                        None => abort!(p, "must be an integer literal")
+                    };
+                    if let Some(call) = names.iter().flat_map(|n| collect_calls_in_expr(&arm.body, n)).next() {
+                        abort!(call, "constants of mutual recursive functions can not call functions of the module");
+                    }
+                }
+                Pat::Wild(_) if formula.is_none() => formula = Some(&arm.body),
+                Pat::Wild(_) => abort!(arm.pat, "match expression can not have multiple wildcard"),
+                _ => abort!(arm.pat, "match expression can only have constants and one wildcard")
+            }
+        }
+        let formula = formula.unwrap_or_else(|| abort!(body, "match expression must have a wildcard expression"));
+        let mut calls = vec![];
+        for (index, called) in names.iter().enumerate() {
+            for call in collect_calls_in_expr(formula, called) {
+                calls.push((index, call_steps(&call, &parameter)));
+            }
+        }
+        Self { name, parameter, arg_type, return_type, constants, body, calls }
+    }
+}
+
+/// the functions of the module which call themselves, directly or through other functions
+///
+/// helper functions outside of a cycle are evaluated as they are
+fn recursive_functions(items: &[Item]) -> Vec<&ItemFn> {
+    let functions: Vec<&ItemFn> = items.iter().filter_map(|item| match item {
+        Item::Fn(f) => Some(f),
+        _ => None
+    }).collect();
+    let names: Vec<String> = functions.iter().map(|f| f.sig.ident.to_string()).collect();
+    let calls: Vec<Vec<usize>> = functions.iter().map(|f| {
+        let block = &f.block;
+        let body: Expr = parse_quote!(#block);
+        (0..names.len()).filter(|g| !collect_calls_in_expr(&body, &names[*g]).is_empty()).collect()
+    }).collect();
+    (0..functions.len()).filter(|f| {
+        let mut reached = BTreeSet::new();
+        let mut pending = calls[*f].clone();
+        while let Some(g) = pending.pop() {
+            if reached.insert(g) {
+                pending.extend(&calls[g]);
+            }
+        }
+        reached.contains(f)
+    }).map(|f| functions[f]).collect()
+}
+
+/// steps k of a call g(n - k)
+fn call_steps(call: &ExprCall, parameter: &Ident) -> u128 {
+    let steps = match call.args.iter().collect::<Vec<_>>().as_slice() {
+        [Expr::Binary(e)] if matches!(e.op, BinOp::Sub(_)) && try_get_ident(&e.left) == Some(parameter.to_string()) => {
+            try_get_int_lit(&e.right).and_then(|k| k.base10_parse::<u128>().ok())
+        }
+        _ => None
+    };
+    match steps {
+        Some(steps) if steps > 0 => steps,
+        _ => abort!(call, "calls of mutual recursive functions need the form f({} - k) with a constant k > 0", parameter)
+    }
+}
+
+/// true if the identifier appears in item
+fn mentions(item: &Item, ident: &Ident) -> bool {
+    struct IdentFinder<'a> {
+        ident: &'a Ident,
+        found: bool,
+    }
+    impl<'a, 'ast> Visit<'ast> for IdentFinder<'a> {
+        fn visit_ident(&mut self, i: &'ast Ident) {
+            if i == self.ident {
+                self.found = true;
+            }
+        }
+    }
+    let mut finder = IdentFinder { ident, found: false };
+    finder.visit_item(item);
+    finder.found
+}
+
+fn window_ident(name: &str) -> Ident {
+    Ident::new(&format!("window_{}", name), Span::call_site())
+}
+
+fn value_ident(name: &str) -> Ident {
+    Ident::new(&format!("value_{}", name), Span::call_site())
+}
+
+/// replaces calls of the group functions with a read from their window
+struct WindowReplacer<'a> {
+    recursion: &'a MutualRecursion,
+    /// parameter of the calling function
+    parameter: &'a Ident,
+}
+
+impl<'a> Fold for WindowReplacer<'a> {
+    fn fold_expr(&mut self, e: Expr) -> Expr {
+        match e {
+            Expr::Call(call) => {
+                let name = try_call_name(&call);
+                match self.recursion.functions.iter().find(|f| Some(&f.name) == name.as_ref()) {
+                    Some(called) => {
+                        let window = window_ident(&called.name);
+                        let steps = call_steps(&call, self.parameter) as usize;
+                        let window_size = self.recursion.window_size as usize;
+                        parse_quote!(#window[(i - #steps) % #window_size].expect("value is computed"))
+                    }
+                    None => fold::fold_expr(self, Expr::Call(call))
+                }
+            }
+            other => fold::fold_expr(self, other)
+        }
+    }
+}
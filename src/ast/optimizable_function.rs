@@ -1,88 +1,312 @@
 use proc_macro_error::abort;
-use syn;
-use syn::{Block, Expr, ExprPath, FnArg, ItemFn, LitInt, Pat, PatType, ReturnType, Stmt, Type};
+use crate::diagnostics::{report, warning};
+use syn::{parse_quote, BinOp, Block, Expr, ExprPath, FnArg, Item, ItemConst, ItemFn, LitInt, Pat, PatType, Receiver, ReturnType, Stmt, Type};
+use syn::fold::Fold;
 use crate::ast::abstract_recursive_match::AbstractRecursiveMatchFunction;
+use crate::ast::constant_folding::{outside_const, ConstInliner, IteratorUnroller};
+use crate::ast::finite_states::FiniteStateFunction;
+use crate::ast::guard_clauses::GuardedBody;
+use crate::ast::history_table::HistoryTableFunction;
+use crate::ast::interval_recursion::IntervalFunction;
+use crate::ast::memoized::{MemoizedFunction, MemoKind};
+use crate::ast::recursive_calls::{collect_calls_in_expr, SelfCallNormalizer};
+use crate::ast::stack_machine::StackMachineFunction;
+use crate::ast::suffix_recursion::SuffixRecursiveFunction;
+use crate::ast::tail_recursion::TailRecursiveFunction;
+use crate::ast::two_sequences::TwoSequenceFunction;
+use crate::options::{OptimizeOptions, Strategy};
 
 /// this represents a recursive function which is build in a way that allows it
 /// to be optimized in an iterative way
 pub struct OptimizableFunction {
     orig_function: ItemFn,
-    recursive_representation: OptimizableRecursiveFunction
+    recursive_representation: Representation
+}
+
+/// the form of the function body the optimization works on
+enum Representation {
+    /// constants and a recursive formula read from the match
+    Recursive(OptimizableRecursiveFunction),
+    /// every recursive call is a tail call
+    TailRecursive(TailRecursiveFunction),
+    /// the body as it is (strategy = "stack" or a match on enum variants)
+    StackMachine(StackMachineFunction),
+    /// the body as it is, the recursive calls get suffixes of two slices
+    TwoSequences(TwoSequenceFunction),
+    /// the body as it is, the recursive calls get smaller intervals i..=j
+    Interval(IntervalFunction),
+    /// a match on n with a second parameter of a finite domain
+    FiniteStates(FiniteStateFunction),
+    /// the body as it is evaluated on demand with a memo (nested calls like f(f(n + 11)))
+    Memoized(MemoizedFunction),
 }
 
 pub struct OptimizableRecursiveFunction {
     name: String,
     input_args: Vec<PatType>,
     _output: Box<Type>,
-    constants: Vec<(LitInt, LitInt)>,
-    recursive_formula: Box<Expr>
+    constants: Vec<(LitInt, Expr)>,
+    /// constants whose arm is marked with #[override_formula]
+    formula_overrides: Vec<LitInt>,
+    recursive_formula: Box<Expr>,
+    /// the expression the match is on
+    matched: Box<Expr>,
+    options: OptimizeOptions
 }
 
 impl OptimizableFunction {
+    pub fn new(f: ItemFn, options: OptimizeOptions) -> Self {
+        // recursive method calls self.f(..) are handled like calls f(..)
+        let normalized = SelfCallNormalizer::new(&f.sig.ident.to_string()).fold_item_fn(f.clone());
+        let tail_recursive = match options.strategy {
+            Strategy::Iterative | Strategy::Auto => TailRecursiveFunction::try_create_from(&normalized),
+            Strategy::Stack | Strategy::Table => None
+        };
+        let recursive_representation = if options.strategy == Strategy::Stack {
+            Representation::StackMachine(StackMachineFunction::create_from(&normalized))
+        } else if options.strategy != Strategy::Table && matches_variants(&normalized) {
+            if options.strategy == Strategy::Auto {
+                report!("optimize_recursion: {} is evaluated with an explicit stack; note: the match has enum variant patterns, the recursion walks a data structure", f.sig.ident);
+            }
+            Representation::StackMachine(StackMachineFunction::create_from(&normalized))
+        } else if options.strategy != Strategy::Table && has_nested_calls(&normalized) && !is_match_form(&normalized) {
+            if options.strategy == Strategy::Auto {
+                report!("optimize_recursion: {} is memoized in a HashMap; note: the recursive calls are nested, their arguments are only known during the evaluation", f.sig.ident);
+            }
+            Representation::Memoized(MemoizedFunction::from_body(&normalized, MemoKind::Hash))
+        } else if options.strategy != Strategy::Table && TwoSequenceFunction::is_applicable(&normalized) {
+            if options.strategy == Strategy::Auto {
+                report!("optimize_recursion: {} is computed over all pairs of suffixes; note: the recursive calls get suffixes of the two slices", f.sig.ident);
+            }
+            Representation::TwoSequences(TwoSequenceFunction::create_from(&normalized))
+        } else if let Some(tail_recursive) = tail_recursive {
+            if options.strategy == Strategy::Auto {
+                report!("optimize_recursion: {} is computed in a loop; note: every recursive call is a tail call", f.sig.ident);
+            }
+            Representation::TailRecursive(tail_recursive)
+        } else if options.strategy != Strategy::Table && FiniteStateFunction::is_applicable(&normalized, &options) {
+            if options.strategy == Strategy::Auto {
+                report!("optimize_recursion: {} is computed for all states together; note: the second parameter has a finite domain", f.sig.ident);
+            }
+            Representation::FiniteStates(FiniteStateFunction::create_from(&normalized, &options))
+        } else if options.strategy != Strategy::Table && IntervalFunction::is_applicable(&normalized) {
+            if options.strategy == Strategy::Auto {
+                report!("optimize_recursion: {} is computed for all intervals by increasing length; note: the recursive calls get smaller intervals of the arguments", f.sig.ident);
+            }
+            Representation::Interval(IntervalFunction::create_from(&normalized))
+        } else {
+            Representation::Recursive(OptimizableRecursiveFunction::create_from(&normalized, options))
+        };
+        Self {
+            recursive_representation,
+            orig_function: f
+        }
+    }
+
+    /// a function which does not call itself but has recursive `fn` items in its body
+    /// (a helper like fn foo(n) -> (i64, i64) whose result is projected): every recursive
+    /// item is optimized on its own, the other statements stay as they are
+    pub fn optimize_nested(f: &ItemFn, options: &OptimizeOptions) -> Option<ItemFn> {
+        let name = f.sig.ident.to_string();
+        let normalized = SelfCallNormalizer::new(&name).fold_item_fn(f.clone());
+        let block = &normalized.block;
+        if !collect_calls_in_expr(&parse_quote!(#block), &name).is_empty() {
+            return None;
+        }
+        let mut optimizer = NestedFunctionOptimizer { options, found: false };
+        let block = optimizer.fold_block((*f.block).clone());
+        if !optimizer.found {
+            return None;
+        }
+        Some(ItemFn { block: Box::new(block), ..f.clone() })
+    }
+
     pub fn optimize(&self) -> ItemFn {
-        let optimized_block = self.recursive_representation.optimize();
+        let optimized_block = match &self.recursive_representation {
+            Representation::Recursive(f) => f.optimize(),
+            Representation::TailRecursive(f) => Box::new(Block {
+                brace_token: Default::default(),
+                stmts: f.construct_stmts()
+            }),
+            Representation::StackMachine(f) => Box::new(Block {
+                brace_token: Default::default(),
+                stmts: f.construct_stmts()
+            }),
+            Representation::TwoSequences(f) => Box::new(Block {
+                brace_token: Default::default(),
+                stmts: f.construct_stmts()
+            }),
+            Representation::Interval(f) => Box::new(Block {
+                brace_token: Default::default(),
+                stmts: f.construct_stmts()
+            }),
+            Representation::FiniteStates(f) => Box::new(Block {
+                brace_token: Default::default(),
+                stmts: f.construct_stmts()
+            }),
+            Representation::Memoized(f) => Box::new(Block {
+                brace_token: Default::default(),
+                stmts: f.construct_stmts()
+            })
+        };
         let mut function = self.orig_function.clone();
         function.block = optimized_block;
+        if let Representation::TailRecursive(_) = &self.recursive_representation {
+            // the loop binds mut parameters again for every iteration
+            for arg in function.sig.inputs.iter_mut() {
+                if let FnArg::Typed(t) = arg {
+                    if let Pat::Ident(i) = &mut *t.pat {
+                        i.mutability = None;
+                    }
+                }
+            }
+        }
+        // the generated index arithmetic can contain % 1 and * 1 for small tuples,
+        // memo lookups clone the values, tail calls assign n = n - 1, table indices are cast to usize
+        // (the lints stay on for the attributes and signature of the function)
+        let block = &function.block;
+        function.block = Box::new(parse_quote!({
+            #[allow(clippy::modulo_one, clippy::identity_op, clippy::clone_on_copy, clippy::assign_op_pattern, clippy::unnecessary_cast)]
+            #block
+        }));
         function
     }
 }
 
 impl From<ItemFn> for OptimizableFunction {
     fn from(f: ItemFn) -> Self {
-        Self {
-            recursive_representation: OptimizableRecursiveFunction::create_from(&f),
-            orig_function: f
-        }
+        Self::new(f, OptimizeOptions::default())
     }
 }
 
 impl OptimizableRecursiveFunction {
-    pub fn create_from(f: &ItemFn) -> Self{
+    pub fn create_from(f: &ItemFn, options: OptimizeOptions) -> Self{
         let mut input_args = vec![];
         for arg in f.sig.inputs.clone() {
-            input_args.push(match arg {
-                FnArg::Receiver(_) => {
-                    abort!(f.sig, "input function arg self is not allowed");
-                }
+            match arg {
+                FnArg::Receiver(r) => check_receiver(&r),
                 FnArg::Typed(t) => {
                     // println!("identifier: {}", get_ident_from_pat(&t));
-                    t
+                    input_args.push(t)
                 }
-            });
+            }
         }
-        if input_args.len() == 0 {
+        if input_args.is_empty() {
             abort!(f.sig, "function need at least 1 argument because we assume no side effects");
         }
         let _output = match f.sig.output {
             ReturnType::Default => abort!(f.sig, "macro optimize_recursion needs a return value"),
             ReturnType::Type(_, ref t) => t.clone()
         };
-        let (constants, recursive_formula) = read_match(f);
+        let (constants, formula_overrides, recursive_formula, matched) = read_match(f, &_output);
         Self {
             name: f.sig.ident.to_string(),
             input_args,
             _output,
             constants,
-            recursive_formula
+            formula_overrides,
+            recursive_formula,
+            matched,
+            options
         }
     }
 
     pub fn optimize(&self) -> Box<Block>{
-        let mut abstract_recursive_match = AbstractRecursiveMatchFunction::create_from(self);
+        let stmts = if self.options.strategy == Strategy::Auto {
+            self.optimize_auto()
+        } else if self.options.strategy == Strategy::Table {
+            HistoryTableFunction::create_from(self).construct_stmts()
+        } else if SuffixRecursiveFunction::is_applicable(self) {
+            SuffixRecursiveFunction::create_from(self).construct_stmts()
+        } else if self.uses_division_steps() {
+            // divide and conquer: only about log n arguments are needed
+            MemoizedFunction::create_from(self, MemoKind::Sorted).construct_stmts()
+        } else if self.needs_full_history() {
+            if let Some(outside) = outside_const(&self.recursive_formula, &self.name) {
+                abort!(outside, "the const {} is declared outside of the function, so its steps are unknown", outside;
+                    help = "declare the const in the function body in front of the match");
+            }
+            HistoryTableFunction::create_from(self).construct_stmts()
+        } else {
+            AbstractRecursiveMatchFunction::create_from(self).construct_iterative_stmts()
+        };
         Box::new(Block {
             brace_token: Default::default(),
-            stmts: abstract_recursive_match.construct_iterative_stmts()
+            stmts
         })
     }
 
-    pub fn constants(&self) -> &Vec<(LitInt, LitInt)> {
+    /// the iterative transform if it fits, a memoized evaluation otherwise (the choice is reported)
+    fn optimize_auto(&self) -> Vec<Stmt> {
+        if SuffixRecursiveFunction::is_applicable(self) {
+            report!("optimize_recursion: {} is computed over the suffixes from the shortest one; note: the recursive calls get suffixes of the slice", self.name);
+            return SuffixRecursiveFunction::create_from(self).construct_stmts();
+        }
+        if self.uses_division_steps() {
+            report!("optimize_recursion: {} is memoized in a sorted Vec; note: the recursive calls divide the argument, so only about log n arguments are needed", self.name);
+            return MemoizedFunction::create_from(self, MemoKind::Sorted).construct_stmts();
+        }
+        if self.needs_full_history() {
+            report!("optimize_recursion: {} is computed bottom-up into a table of all values up to the argument; note: the arguments of the recursive calls are not a fixed step away from the argument", self.name);
+            return HistoryTableFunction::create_from(self).construct_stmts();
+        }
+        match AbstractRecursiveMatchFunction::check_applicable(self) {
+            Ok(()) => {
+                report!("optimize_recursion: {} is computed iteratively", self.name);
+                AbstractRecursiveMatchFunction::create_from(self).construct_iterative_stmts()
+            }
+            Err(reason) => {
+                let kind = MemoKind::choose(self);
+                let memo = match kind {
+                    MemoKind::Dense => "a dense Vec",
+                    _ => "a HashMap"
+                };
+                report!("optimize_recursion: {} is memoized in {}; note: the iterative transform does not fit: {}", self.name, memo, reason);
+                MemoizedFunction::create_from(self, kind).construct_stmts()
+            }
+        }
+    }
+
+    /// true if a recursive call divides the argument (f(n / 2), f(n >> 1))
+    fn uses_division_steps(&self) -> bool {
+        collect_calls_in_expr(&self.recursive_formula, &self.name).iter()
+            .flat_map(|call| call.args.iter())
+            .any(|arg| matches!(arg, Expr::Binary(e) if matches!(e.op, BinOp::Div(_) | BinOp::Shr(_))))
+    }
+
+    /// true if a recursive call of a function with an integer parameter n has an argument
+    /// other than n - k or n + k (f(i) inside a closure or loop, f(n - f(n - 1)), ...)
+    fn needs_full_history(&self) -> bool {
+        if self.input_args.len() != 1 || integer_type_range(&self.input_args[0].ty).is_none() {
+            return false;
+        }
+        let parameter = match &*self.input_args[0].pat {
+            Pat::Ident(i) => i.ident.to_string(),
+            _ => return false
+        };
+        let is_step = |arg: &Expr| matches!(arg, Expr::Binary(e) if matches!(e.op, BinOp::Add(_) | BinOp::Sub(_))
+            && ((try_get_ident(&e.left).as_deref() == Some(parameter.as_str()) && try_get_int_lit(&e.right).is_some())
+                || (try_get_int_lit(&e.left).is_some() && try_get_ident(&e.right).as_deref() == Some(parameter.as_str()))));
+        collect_calls_in_expr(&self.recursive_formula, &self.name).iter()
+            .any(|call| call.args.iter().any(|arg| !is_step(arg)))
+    }
+
+    pub fn constants(&self) -> &Vec<(LitInt, Expr)> {
         &self.constants
     }
 
-    pub fn recursive_formula(&self) -> &Box<Expr> {
+    pub fn formula_overrides(&self) -> &Vec<LitInt> {
+        &self.formula_overrides
+    }
+
+    pub fn recursive_formula(&self) -> &Expr {
         &self.recursive_formula
     }
 
+    pub fn matched_expr(&self) -> &Expr {
+        &self.matched
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
@@ -91,21 +315,138 @@ impl OptimizableRecursiveFunction {
         &self.input_args
     }
 
-    pub fn return_type(&self) -> &Box<Type> {
+    pub fn return_type(&self) -> &Type {
         &self._output
     }
+
+    pub fn options(&self) -> &OptimizeOptions {
+        &self.options
+    }
 }
 
-fn read_match(f: &ItemFn) -> (Vec<(LitInt, LitInt)>, Box<Expr>) {
+/// &self is a read-only context which is the same for every recursive call,
+/// a recursion which changes or consumes the object can not be optimized
+pub fn check_receiver(r: &Receiver) {
+    if r.reference.is_none() || r.mutability.is_some() {
+        abort!(r, "only &self is allowed as receiver because the recursion must not change the object");
+    }
+}
+
+/// true if the body is a match with enum variant patterns (a recursion over a data structure
+/// like match t { Leaf => 0, Node(l, r) => .. }), the body is evaluated as it is then
+fn matches_variants(f: &ItemFn) -> bool {
+    fn is_variant(p: &Pat) -> bool {
+        match p {
+            Pat::TupleStruct(_) | Pat::Struct(_) | Pat::Path(_) => true,
+            // a unit variant which is imported, bindings start with a lowercase letter
+            Pat::Ident(i) => i.subpat.is_none() && i.ident.to_string().starts_with(char::is_uppercase),
+            Pat::Or(o) => o.cases.iter().any(is_variant),
+            Pat::Reference(r) => is_variant(&r.pat),
+            _ => false
+        }
+    }
+    match f.block.stmts.as_slice() {
+        [Stmt::Expr(Expr::Match(m))] => m.arms.iter().any(|arm| is_variant(&arm.pat)),
+        _ => false
+    }
+}
+
+/// replaces every recursive `fn` item with its optimized version
+struct NestedFunctionOptimizer<'a> {
+    options: &'a OptimizeOptions,
+    /// true if a recursive item was found
+    found: bool,
+}
+
+impl<'a> Fold for NestedFunctionOptimizer<'a> {
+    fn fold_item_fn(&mut self, f: ItemFn) -> ItemFn {
+        let block = &f.block;
+        if collect_calls_in_expr(&parse_quote!(#block), &f.sig.ident.to_string()).is_empty() {
+            return syn::fold::fold_item_fn(self, f);
+        }
+        self.found = true;
+        OptimizableFunction::new(f, self.options.clone()).optimize()
+    }
+}
+
+/// true if a recursive call is an argument of another recursive call (f(f(n + 11)))
+fn has_nested_calls(f: &ItemFn) -> bool {
+    let name = f.sig.ident.to_string();
+    let block = &f.block;
+    collect_calls_in_expr(&parse_quote!(#block), &name).iter()
+        .any(|call| call.args.iter().any(|arg| !collect_calls_in_expr(arg, &name).is_empty()))
+}
+
+/// true if the body has the form read by read_match: a match with constants and a wildcard,
+/// or guard clauses in front of the formula
+fn is_match_form(f: &ItemFn) -> bool {
+    let const_items = f.block.stmts.iter().take_while(|stmt| matches!(stmt, Stmt::Item(Item::Const(_)))).count();
+    let stmts = &f.block.stmts[const_items..];
+    match stmts {
+        [Stmt::Expr(Expr::Match(m))] => m.arms.iter().all(|arm| matches!(arm.pat, Pat::Lit(_) | Pat::Wild(_))),
+        _ => GuardedBody::read(f, stmts).is_some()
+    }
+}
+
+/// (argument, value) of the match arms with a constant
+type Constants = Vec<(LitInt, Expr)>;
+
+/// reads the constants, the formula and the matched expression of the match,
+/// const items in front of it are inlined into the formula
+///
+/// guard clauses and if-chains on n == c are read like the arms of a match on n
+/// (if n < 2 { return n; } f(n - 1) + f(n - 2))
+///
+/// the values of the constants are integer literals, other return types (tuples and structs)
+/// can use any expression which does not call the function
+fn read_match(f: &ItemFn, return_type: &Type) -> (Constants, Vec<LitInt>, Box<Expr>, Box<Expr>) {
+    let name = f.sig.ident.to_string();
+    let constant_value = |body: &Expr| -> Expr {
+        if integer_type_range(return_type).is_some() {
+            let value = get_int_lit(body);
+            parse_quote!(#value)
+        } else {
+            if let Some(call) = collect_calls_in_expr(body, &name).first() {
+                abort!(call, "constants can not call the function, they are the start of the computation");
+            }
+            body.clone()
+        }
+    };
     let mut constants = vec![];
+    let mut formula_overrides = vec![];
     let mut recursive_formula = None;
-    if f.block.stmts.len() == 1 {
-        if let Stmt::Expr(Expr::Match(match_expr))= &f.block.stmts[0] {
+    let const_items: Vec<ItemConst> = f.block.stmts.iter().map_while(|stmt| match stmt {
+        Stmt::Item(Item::Const(c)) => Some(c.clone()),
+        _ => None
+    }).collect();
+    let stmts = &f.block.stmts[const_items.len()..];
+    if !matches!(stmts, [Stmt::Expr(Expr::Match(_))]) {
+        if let Some(guarded) = GuardedBody::read(f, stmts) {
+            let constants = guarded.constants.iter().map(|(c, v)| (c.clone(), constant_value(v))).collect();
+            let formula = ConstInliner::new(&const_items).fold_expr(guarded.formula);
+            return (constants, vec![], Box::new(IteratorUnroller.fold_expr(formula)), Box::new(guarded.subject));
+        }
+    }
+    if stmts.len() == 1 {
+        if let Stmt::Expr(Expr::Match(match_expr))= &stmts[0] {
             // println!("match expr: {:?}", match_expr.expr);
             for arm in match_expr.arms.iter() {
                 match &arm.pat {
                     Pat::Lit(syn::PatLit{attrs: _, expr }) => {
-                        constants.push((get_int_lit(expr), get_int_lit(&arm.body)));
+                        let constant = get_int_lit(expr);
+                        // the original match never reaches these arms, so they are ignored
+                        if recursive_formula.is_some() {
+                            warning!(arm.pat, "unreachable constant {}: the wildcard arm above already matches it", constant);
+                            continue
+                        }
+                        if constants.iter().any(|(c, _): &(LitInt, Expr)| c.base10_digits() == constant.base10_digits()) {
+                            warning!(arm.pat, "duplicate constant {}: only the first arm is used", constant);
+                            continue
+                        }
+                        if arm.attrs.iter().any(|a| a.path.is_ident("override_formula")) {
+                            formula_overrides.push(constant.clone());
+                        }
+                        constants.push((constant, constant_value(&arm.body)));
                     }
                     Pat::Wild(syn::PatWild{attrs: _, underscore_token: _}) => {
                         if recursive_formula.is_none() {
@@ -120,13 +461,19 @@ fn read_match(f: &ItemFn) -> (Vec<(LitInt, LitInt)>, Box<Expr>) {
                 };
             }
         } else {
-            abort!(f.block.stmts[0], "function must contain a match expression");
+            abort!(stmts[0], "function must contain a match expression");
         }
     } else {
-        abort!(f.block, "function can only have one match statement");
+        abort!(f.block, "function can only have one match statement";
+            help = "the base cases can also be guard clauses `if n == c { return v; }` or `if n < c { return n; }` for an unsigned n");
     }
     if let Some(formula) = recursive_formula {
-        (constants, formula)
+        let formula = ConstInliner::new(&const_items).fold_expr(*formula);
+        let matched = match &stmts[0] {
+            Stmt::Expr(Expr::Match(match_expr)) => match_expr.expr.clone(),
+            _ => unreachable!("the match was read above")
+        };
+        (constants, formula_overrides, Box::new(IteratorUnroller.fold_expr(formula)), matched)
     } else {
         abort!(f.block, "match expression must have a wildcard expression");
     }
@@ -135,13 +482,13 @@ fn read_match(f: &ItemFn) -> (Vec<(LitInt, LitInt)>, Box<Expr>) {
 fn _get_ident_from_pat(p: &PatType) -> String {
     let p = &p.pat;
     if let Pat::Ident(i) = &**p {
-        return i.ident.to_string()
+        i.ident.to_string()
     } else {
         abort!(p, "PatType must be an identifier");
     }
 }
 
-fn get_int_lit(b: &Box<Expr>) -> LitInt {
+fn get_int_lit(b: &Expr) -> LitInt {
     if let Some(lit) = try_get_int_lit(b) {
         lit
     } else {
@@ -149,8 +496,8 @@ fn get_int_lit(b: &Box<Expr>) -> LitInt {
     }
 }
 
-pub fn try_get_int_lit(b: &Box<Expr>) -> Option<LitInt> {
-    match &**b {
+pub fn try_get_int_lit(b: &Expr) -> Option<LitInt> {
+    match b {
         Expr::Lit(syn::ExprLit{ attrs: _, lit: syn::Lit::Int(lit) }) => {
             Some(lit.clone())
         }
@@ -160,8 +507,31 @@ pub fn try_get_int_lit(b: &Box<Expr>) -> Option<LitInt> {
     }
 }
 
-pub fn try_get_ident(b: &Box<Expr>) -> Option<String> {
-    match &**b {
+/// returns the inclusive value range of a primitive integer type (usize/isize are assumed to have 64 bit)
+///
+/// u128::MAX does not fit i128, so the upper bound is clamped to i128::MAX
+pub fn integer_type_range(ty: &Type) -> Option<(i128, i128)> {
+    let ident = match ty {
+        Type::Path(p) => p.path.get_ident()?.to_string(),
+        _ => return None
+    };
+    Some(match ident.as_str() {
+        "u8" => (0, u8::MAX as i128),
+        "u16" => (0, u16::MAX as i128),
+        "u32" => (0, u32::MAX as i128),
+        "u64" | "usize" => (0, u64::MAX as i128),
+        "u128" => (0, i128::MAX),
+        "i8" => (i8::MIN as i128, i8::MAX as i128),
+        "i16" => (i16::MIN as i128, i16::MAX as i128),
+        "i32" => (i32::MIN as i128, i32::MAX as i128),
+        "i64" | "isize" => (i64::MIN as i128, i64::MAX as i128),
+        "i128" => (i128::MIN, i128::MAX),
+        _ => return None
+    })
+}
+
+pub fn try_get_ident(b: &Expr) -> Option<String> {
+    match b {
         Expr::Path(ExprPath{ attrs: _, qself: _, path }) => {
             Some(path.segments.first()?.ident.to_string())
         }
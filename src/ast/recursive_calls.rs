@@ -0,0 +1,129 @@
+use syn::{Expr, ExprBlock, ExprCall, ExprMethodCall, ExprPath, Path, Stmt, parse_quote};
+use syn::fold::{self, Fold};
+use syn::visit::{self, Visit};
+
+/// returns the called function name of a call expression (None if the callee is not a path)
+pub fn try_call_name(f: &ExprCall) -> Option<String> {
+    match &*f.func {
+        Expr::Path(ExprPath{ attrs: _, qself: _, path: Path{ leading_colon: _, segments }}) => {
+            Some(segments.last()?.ident.to_string())
+        }
+        _ => None
+    }
+}
+
+/// collects all calls of a function, including calls nested in arguments, closures and blocks
+///
+/// the first expression the iterative transform does not support (a match, a loop, an item, a call
+/// of a closure, ...) is remembered, so the same walk decides whether a formula fits it
+struct CallCollector<'a> {
+    name: &'a str,
+    calls: Vec<ExprCall>,
+    unsupported: Option<Expr>,
+}
+
+impl<'a, 'ast> Visit<'ast> for CallCollector<'a> {
+    fn visit_expr(&mut self, e: &'ast Expr) {
+        let supported = match e {
+            Expr::Call(call) => try_call_name(call).is_some(),
+            Expr::Block(ExprBlock{ attrs: _, label, block: _ }) => label.is_none(),
+            Expr::Array(_) | Expr::Binary(_) | Expr::Cast(_) | Expr::Closure(_) | Expr::Field(_)
+            | Expr::If(_) | Expr::Index(_) | Expr::Lit(_) | Expr::MethodCall(_) | Expr::Paren(_)
+            | Expr::Path(_) | Expr::Range(_) | Expr::Reference(_) | Expr::Struct(_) | Expr::Tuple(_)
+            | Expr::Type(_) | Expr::Unary(_) => true,
+            _ => false
+        };
+        if !supported && self.unsupported.is_none() {
+            self.unsupported = Some(e.clone());
+        }
+        visit::visit_expr(self, e);
+    }
+
+    fn visit_expr_call(&mut self, e: &'ast ExprCall) {
+        if try_call_name(e).as_deref() == Some(self.name) {
+            self.calls.push(e.clone());
+        }
+        visit::visit_expr_call(self, e);
+    }
+
+    fn visit_stmt(&mut self, s: &'ast Stmt) {
+        if let Stmt::Item(i) = s {
+            if self.unsupported.is_none() {
+                self.unsupported = Some(parse_quote!({ #i }));
+            }
+        }
+        visit::visit_stmt(self, s);
+    }
+}
+
+/// all calls of function name inside expr
+pub fn collect_calls_in_expr(expr: &Expr, name: &str) -> Vec<ExprCall> {
+    let mut collector = CallCollector { name, calls: vec![], unsupported: None };
+    collector.visit_expr(expr);
+    collector.calls
+}
+
+/// all calls of function name inside expr, the first expression the iterative transform
+/// does not support otherwise
+pub fn try_collect_calls_in_expr(expr: &Expr, name: &str) -> Result<Vec<ExprCall>, Box<Expr>> {
+    let mut collector = CallCollector { name, calls: vec![], unsupported: None };
+    collector.visit_expr(expr);
+    match collector.unsupported {
+        Some(unsupported) => Err(Box::new(unsupported)),
+        None => Ok(collector.calls)
+    }
+}
+
+/// replaces every call of a function with the expression created by replace
+/// (calls inside the arguments are replaced first)
+pub struct CallReplacer<'a, F: FnMut(ExprCall) -> Expr> {
+    name: &'a str,
+    replace: F,
+}
+
+impl<'a, F: FnMut(ExprCall) -> Expr> CallReplacer<'a, F> {
+    pub fn new(name: &'a str, replace: F) -> Self {
+        Self { name, replace }
+    }
+}
+
+impl<'a, F: FnMut(ExprCall) -> Expr> Fold for CallReplacer<'a, F> {
+    fn fold_expr(&mut self, e: Expr) -> Expr {
+        match e {
+            Expr::Call(call) if try_call_name(&call).as_deref() == Some(self.name) => {
+                let call = fold::fold_expr_call(self, call);
+                (self.replace)(call)
+            }
+            other => fold::fold_expr(self, other)
+        }
+    }
+}
+
+/// rewrites recursive method calls self.name(args) into calls name(args)
+pub struct SelfCallNormalizer<'a> {
+    name: &'a str,
+}
+
+impl<'a> SelfCallNormalizer<'a> {
+    pub fn new(name: &'a str) -> Self {
+        Self { name }
+    }
+}
+
+impl<'a> Fold for SelfCallNormalizer<'a> {
+    fn fold_expr(&mut self, e: Expr) -> Expr {
+        match e {
+            Expr::MethodCall(call) if call.method == self.name && call.turbofish.is_none()
+                && matches!(&*call.receiver, Expr::Path(p) if p.path.is_ident("self")) => {
+                let ExprMethodCall{ attrs, receiver: _, dot_token: _, method, turbofish: _, paren_token, args } = fold::fold_expr_method_call(self, call);
+                Expr::Call(ExprCall {
+                    attrs,
+                    func: Box::new(parse_quote!(#method)),
+                    paren_token,
+                    args
+                })
+            }
+            other => fold::fold_expr(self, other)
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use proc_macro_error::abort;
+use syn::{Block, Expr, ExprCall, ExprForLoop, ExprLoop, ExprWhile, FnArg, ItemFn, PatType, ReturnType, Stmt, Type, parse_quote};
+use syn::fold::Fold;
+use syn::visit::{self, Visit};
+use crate::ast::optimizable_function::check_receiver;
+use crate::ast::memoized::{key_expr, key_pattern, key_type, ReturnWrapper};
+use crate::ast::recursive_calls::{collect_calls_in_expr, CallReplacer};
+
+/// evaluates the function with an explicit heap allocated stack of continuation frames instead of recursion
+///
+/// a frame holds the arguments of a pending call and the values of the recursive calls it already
+/// received (the defunctionalized continuation). the body of the top frame is evaluated until it
+/// reaches a recursive call without a received value, which pushes a new frame. the value of a
+/// finished frame is passed to the frame below. nothing is memoized, so every call is evaluated
+/// exactly like the recursion does
+///
+/// a frame replays its body up to the next missing call for every value it receives. recursive
+/// calls inside loops are rejected, so a frame receives a bounded number of values and the replays
+/// only cost a constant factor instead of growing quadratically with the calls of a loop
+pub struct StackMachineFunction {
+    name: String,
+    input_args: Vec<PatType>,
+    return_type: Box<Type>,
+    body: Block,
+}
+
+impl StackMachineFunction {
+    pub fn create_from(f: &ItemFn) -> Self {
+        let input_args: Vec<PatType> = f.sig.inputs.iter().filter_map(|arg| match arg {
+            FnArg::Receiver(r) => {
+                check_receiver(r);
+                None
+            }
+            FnArg::Typed(t) => Some(t.clone())
+        }).collect();
+        if input_args.is_empty() {
+            abort!(f.sig, "function need at least 1 argument because we assume no side effects");
+        }
+        let return_type = match &f.sig.output {
+            ReturnType::Default => abort!(f.sig, "macro optimize_recursion needs a return value"),
+            ReturnType::Type(_, t) => t.clone()
+        };
+        Self {
+            name: f.sig.ident.to_string(),
+            input_args,
+            return_type,
+            body: (*f.block).clone(),
+        }
+    }
+
+    /// convert the function body to statements running the continuation stack
+    pub fn construct_stmts(&self) -> Vec<Stmt> {
+        let key_type = key_type(&self.input_args);
+        let key_pattern = key_pattern(&self.input_args);
+        let key = key_expr(&self.input_args);
+        let return_type = &self.return_type;
+        let body = self.replace_recursive_calls();
+        // the body is evaluated in a labeled block instead of a closure, a closure could not return
+        // a reference argument (like a child of a tree) which borrows from its parameter
+        let block: Block = parse_quote! {
+            {
+                let mut stack: Vec<(#key_type, Vec<#return_type>)> = vec![(#key, Vec::new())];
+                loop {
+                    let (argument, received) = stack.last().expect("the stack always holds the pending call");
+                    let #key_pattern: #key_type = argument.clone();
+                    let mut received = received.iter();
+                    let result: Result<#return_type, #key_type> = 'evaluate: {
+                        Ok(#body)
+                    };
+                    match result {
+                        Ok(value) => {
+                            stack.pop();
+                            match stack.last_mut() {
+                                Some((_, received)) => received.push(value),
+                                None => break value
+                            }
+                        }
+                        Err(call) => stack.push((call, Vec::new()))
+                    }
+                }
+            }
+        };
+        block.stmts
+    }
+
+    /// rewrite the body: the nth recursive call reached takes the nth received value
+    /// or leaves the evaluation with its arguments as a new frame
+    fn replace_recursive_calls(&self) -> Expr {
+        let block = &self.body;
+        let body: Expr = parse_quote!(#block);
+        for call in collect_calls_in_expr(&body, &self.name) {
+            if call.args.len() != self.input_args.len() {
+                abort!(call, "recursive call needs {} arguments", self.input_args.len());
+            }
+        }
+        LoopCallCheck { name: &self.name }.visit_expr(&body);
+        let key_type = key_type(&self.input_args);
+        let mut replacer = CallReplacer::new(&self.name, |call: ExprCall| {
+            let args = call.args.iter();
+            let argument: Expr = if self.input_args.len() == 1 {
+                parse_quote!(#(#args)*)
+            } else {
+                parse_quote!((#(#args),*))
+            };
+            // parenthesized, a leading block would end a match arm when the tokens are parsed again
+            parse_quote!(({
+                let argument: #key_type = #argument;
+                match received.next() {
+                    Some(value) => value.clone(),
+                    None => break 'evaluate Err(argument)
+                }
+            }))
+        });
+        let body = ReturnWrapper::new(&self.name).with_label(parse_quote!('evaluate)).fold_expr(body);
+        replacer.fold_expr(body)
+    }
+}
+
+/// rejects recursive calls inside loops, every value received by the frame would replay the loop from its start
+struct LoopCallCheck<'a> {
+    name: &'a str,
+}
+
+impl<'a> LoopCallCheck<'a> {
+    fn check(&self, repeated: &Expr) {
+        if let Some(call) = collect_calls_in_expr(repeated, self.name).first() {
+            abort!(call, "recursive calls inside loops are not supported by the explicit stack";
+                note = "a frame replays its body for every value it receives, a loop would be evaluated again from its start for every call");
+        }
+    }
+}
+
+impl<'a, 'ast> Visit<'ast> for LoopCallCheck<'a> {
+    fn visit_expr_for_loop(&mut self, e: &'ast ExprForLoop) {
+        let body = &e.body;
+        self.check(&parse_quote!(#body));
+        visit::visit_expr_for_loop(self, e);
+    }
+
+    fn visit_expr_while(&mut self, e: &'ast ExprWhile) {
+        let (cond, body) = (&e.cond, &e.body);
+        self.check(&parse_quote!({ #cond; #body }));
+        visit::visit_expr_while(self, e);
+    }
+
+    fn visit_expr_loop(&mut self, e: &'ast ExprLoop) {
+        let body = &e.body;
+        self.check(&parse_quote!(#body));
+        visit::visit_expr_loop(self, e);
+    }
+}
@@ -0,0 +1,177 @@
+use num::Integer;
+use proc_macro_error::abort;
+use quote::quote;
+use syn::{Block, Expr, ExprCall, ExprMethodCall, ExprRange, Ident, LitInt, Pat, RangeLimits, Stmt, Type, parse_quote};
+use syn::fold::Fold;
+use crate::ast::abstract_recursive_match::parse_non_typed_int;
+use crate::ast::optimizable_function::{OptimizableRecursiveFunction, try_get_ident, try_get_int_lit};
+use crate::ast::recursive_calls::{collect_calls_in_expr, CallReplacer};
+
+/// a function of a slice or &str whose recursive calls get suffixes &s[k..] of it
+///
+/// the match on s.len() gives the constants, the suffixes are computed from the shortest one up to
+/// the whole slice while a window holds the values of the last suffixes; a suffix which would read a
+/// length below the smallest constant panics like the recursion does
+///
+/// example: count(s) = count(&s[1..]) + if s.len() >= 2 && s[0] == b'1' { count(&s[2..]) } else { 0 }
+pub struct SuffixRecursiveFunction {
+    name: String,
+    parameter: Ident,
+    parameter_type: Box<Type>,
+    return_type: Box<Type>,
+    constants: Vec<(LitInt, Expr)>,
+    formula: Expr,
+    /// the largest offset k of a call f(&s[k..])
+    window_size: usize,
+    /// the gcd of the offsets, the recursion only reaches lengths a multiple of it below the whole slice
+    step: usize,
+    /// length of the shortest suffix given as constant
+    start: i128,
+}
+
+impl SuffixRecursiveFunction {
+    /// true if the only parameter is a slice or &str
+    pub fn is_applicable(f: &OptimizableRecursiveFunction) -> bool {
+        f.input_args().len() == 1 && is_slice_type(&f.input_args()[0].ty)
+    }
+
+    pub fn create_from(f: &OptimizableRecursiveFunction) -> Self {
+        let parameter = match &*f.input_args()[0].pat {
+            Pat::Ident(i) => i.ident.clone(),
+            p => abort!(p, "must be an identifier")
+        };
+        let matches_length = matches!(f.matched_expr(), Expr::MethodCall(ExprMethodCall{ receiver, method, args, .. })
+            if method == "len" && args.is_empty() && try_get_ident(receiver).as_ref() == Some(&parameter.to_string()));
+        if !matches_length {
+            abort!(f.matched_expr(), "a recursion over suffixes needs to match on {}.len()", parameter);
+        }
+        let offsets: Vec<u128> = collect_calls_in_expr(f.recursive_formula(), f.name()).iter()
+            .map(|call| suffix_offset(call, &parameter))
+            .collect();
+        let window_size = offsets.iter().copied().max()
+            .unwrap_or_else(|| abort!(f.recursive_formula(), "need at least 1 recursive call!"));
+        let step = offsets.iter().fold(0, |gcd, offset| gcd.gcd(offset));
+        let start = match f.constants().iter().filter_map(|(c, _)| c.base10_parse::<i128>().ok()).min() {
+            Some(start) => start,
+            None => abort!(f.recursive_formula(), "a recursion over suffixes needs at least 1 constant length to start with")
+        };
+        Self {
+            name: f.name().to_string(),
+            parameter,
+            parameter_type: f.input_args()[0].ty.clone(),
+            return_type: Box::new(f.return_type().clone()),
+            constants: f.constants().clone(),
+            formula: f.recursive_formula().clone(),
+            window_size: window_size as usize,
+            step: step as usize,
+            start,
+        }
+    }
+
+    /// convert the function body to statements computing the suffixes from the shortest one
+    pub fn construct_stmts(&self) -> Vec<Stmt> {
+        let parameter = &self.parameter;
+        let parameter_type = &self.parameter_type;
+        let return_type = &self.return_type;
+        let window_size = self.window_size;
+        let start = parse_non_typed_int(&self.start);
+        let arms = self.constants.iter().map(|(c, v)| quote!(#c => #v,));
+        let formula = self.replace_recursive_calls();
+        // the other lengths are not reached and could read a suffix below the smallest constant
+        let lengths: Expr = if self.step > 1 {
+            let step = self.step;
+            parse_quote!((#start..=#parameter.len()).filter(|length| (#parameter.len() - length) % #step == 0))
+        } else {
+            parse_quote!(#start..=#parameter.len())
+        };
+        // suffixes shorter than the shortest constant recurse until a slice index is out of range
+        let below_start: Option<Stmt> = if self.start > 0 {
+            let message = format!("length {{}} lies below the smallest constant {}, the recursion indexes past the end of the slice", self.start);
+            Some(parse_quote! {
+                if #parameter.len() < #start {
+                    panic!(#message, #parameter.len());
+                }
+            })
+        } else {
+            None
+        };
+        let block: Block = parse_quote! {
+            {
+                #below_start
+                // early returns of the formula only leave the evaluation of one suffix
+                let evaluate = |#parameter: #parameter_type, window: &[#return_type; #window_size]| -> #return_type {
+                    match #parameter.len() {
+                        #(#arms)*
+                        _ => #formula
+                    }
+                };
+                let mut window: [#return_type; #window_size] = [Default::default(); #window_size];
+                for length in #lengths {
+                    // a &str suffix has to start at a char boundary, the recursion can not reach the others
+                    let suffix = match #parameter.get(#parameter.len() - length..) {
+                        Some(suffix) => suffix,
+                        None => continue
+                    };
+                    window[length % #window_size] = evaluate(suffix, &window);
+                }
+                window[#parameter.len() % #window_size]
+            }
+        };
+        block.stmts
+    }
+
+    /// rewrite the formula: f(&s[k..]) reads the value of the suffix length s.len() - k from the window
+    ///
+    /// the length is taken from s[k..], so an out of range offset panics like the recursion does
+    fn replace_recursive_calls(&self) -> Expr {
+        let parameter = &self.parameter;
+        let window_size = self.window_size;
+        let start = parse_non_typed_int(&self.start);
+        let message = format!("{}(&{}[..]) needs a suffix of length {{}} which is shorter than the smallest constant", self.name, parameter);
+        let mut replacer = CallReplacer::new(&self.name, |call: ExprCall| {
+            let offset = parse_non_typed_int(&(suffix_offset(&call, parameter) as i128));
+            if self.start > 0 {
+                parse_quote!(window[{
+                    let length = #parameter[#offset..].len();
+                    assert!(length >= #start, #message, length);
+                    length % #window_size
+                }])
+            } else {
+                parse_quote!(window[#parameter[#offset..].len() % #window_size])
+            }
+        });
+        replacer.fold_expr(self.formula.clone())
+    }
+}
+
+/// &[T] or &str
+fn is_slice_type(ty: &Type) -> bool {
+    match ty {
+        Type::Reference(r) if r.mutability.is_none() => match &*r.elem {
+            Type::Slice(_) => true,
+            Type::Path(p) => p.path.is_ident("str"),
+            _ => false
+        },
+        _ => false
+    }
+}
+
+/// offset k > 0 of a recursive call f(&s[k..])
+fn suffix_offset(call: &ExprCall, parameter: &Ident) -> u128 {
+    let offset = match call.args.iter().collect::<Vec<_>>().as_slice() {
+        [Expr::Reference(r)] if r.mutability.is_none() => match &*r.expr {
+            Expr::Index(i) if try_get_ident(&i.expr).as_ref() == Some(&parameter.to_string()) => match &*i.index {
+                Expr::Range(ExprRange{ attrs: _, from: Some(from), limits: RangeLimits::HalfOpen(_), to: None }) => {
+                    try_get_int_lit(from).and_then(|k| k.base10_parse::<u128>().ok())
+                }
+                _ => None
+            },
+            _ => None
+        },
+        _ => None
+    };
+    match offset {
+        Some(offset) if offset > 0 => offset,
+        _ => abort!(call, "recursive calls over suffixes need the form f(&{}[k..]) with a constant k > 0", parameter)
+    }
+}
@@ -0,0 +1,215 @@
+use quote::quote;
+use syn::{Block, Expr, ExprCall, ExprClosure, ExprReturn, FnArg, Ident, Item, ItemFn, Pat, Stmt, parse_quote};
+use proc_macro2::Span;
+use syn::fold::{self, Fold};
+use syn::visit::{self, Visit};
+use crate::ast::recursive_calls::{collect_calls_in_expr, try_call_name};
+
+/// a function whose recursive calls are all in tail position (their value is the value of the function)
+///
+/// the body is wrapped into a loop, a tail call assigns its arguments to hygienic loop variables
+/// and continues the loop, every iteration binds the parameters to them again (so a let in the body
+/// which shadows a parameter does not change what the tail call assigns)
+///
+/// example: gcd(a, b) = if b == 0 { a } else { gcd(b, a % b) }
+pub struct TailRecursiveFunction {
+    name: String,
+    parameters: Vec<Ident>,
+    /// parameters which are declared mut
+    mutable_parameters: Vec<bool>,
+    body: Block,
+}
+
+impl TailRecursiveFunction {
+    /// None if some recursive call is not a tail call (or the function is not recursive)
+    pub fn try_create_from(f: &ItemFn) -> Option<Self> {
+        let name = f.sig.ident.to_string();
+        let mut parameters = vec![];
+        let mut mutable_parameters = vec![];
+        for arg in &f.sig.inputs {
+            match arg {
+                FnArg::Typed(t) => match &*t.pat {
+                    Pat::Ident(i) if i.by_ref.is_none() && i.subpat.is_none() => {
+                        parameters.push(i.ident.clone());
+                        mutable_parameters.push(i.mutability.is_some());
+                    }
+                    _ => return None
+                },
+                // &self stays the same for every call
+                FnArg::Receiver(r) if r.reference.is_some() && r.mutability.is_none() => {}
+                FnArg::Receiver(_) => return None
+            }
+        }
+        let block = &f.block;
+        let body: Expr = parse_quote!(#block);
+        let calls = collect_calls_in_expr(&body, &name).len();
+        let mut tail_calls = TailCallCounter { name: &name, count: 0 };
+        tail_calls.tail(&body);
+        tail_calls.visit_expr(&body);
+        if calls == 0 || tail_calls.count != calls {
+            return None;
+        }
+        Some(Self {
+            name,
+            parameters,
+            mutable_parameters,
+            body: (*f.block).clone(),
+        })
+    }
+
+    /// convert the function body to a loop
+    pub fn construct_stmts(&self) -> Vec<Stmt> {
+        let parameters = &self.parameters;
+        let loop_variables = &self.loop_variables();
+        let mutability = self.mutable_parameters.iter().map(|mutable| if *mutable { Some(quote!(mut)) } else { None });
+        let block = &self.body;
+        let body: Expr = parse_quote!(#block);
+        // a body which always jumps or returns has no value to break with
+        let breaks = !diverges(&body, &self.name);
+        let body = TailCallReplacer { name: &self.name, loop_variables }.fold_expr(body);
+        let body: Block = if breaks {
+            parse_quote!({
+                break #body;
+            })
+        } else {
+            parse_quote!(#body)
+        };
+        let block: Block = parse_quote! {
+            {
+                #(let mut #loop_variables = #parameters;)*
+                'tail_call: loop {
+                    #(let #mutability #parameters = #loop_variables;)*
+                    #body
+                }
+            }
+        };
+        block.stmts
+    }
+
+    /// the values of the parameters for the next iteration, the body can not name them
+    fn loop_variables(&self) -> Vec<Ident> {
+        self.parameters.iter().map(|p| Ident::new(&format!("tail_call_{}", p), Span::mixed_site())).collect()
+    }
+}
+
+/// true if every path through e ends in a tail call or a return
+fn diverges(e: &Expr, name: &str) -> bool {
+    match e {
+        Expr::Call(call) => try_call_name(call).as_deref() == Some(name),
+        Expr::Return(_) => true,
+        Expr::Block(b) => block_diverges(&b.block, name),
+        Expr::If(i) => match &i.else_branch {
+            Some((_, else_branch)) => block_diverges(&i.then_branch, name) && diverges(else_branch, name),
+            None => false
+        },
+        Expr::Match(m) => m.arms.iter().all(|arm| diverges(&arm.body, name)),
+        Expr::Paren(p) => diverges(&p.expr, name),
+        _ => false
+    }
+}
+
+fn block_diverges(b: &Block, name: &str) -> bool {
+    match b.stmts.last() {
+        Some(Stmt::Expr(e)) | Some(Stmt::Semi(e, _)) => diverges(e, name),
+        _ => false
+    }
+}
+
+/// counts the recursive calls in tail position: the ones reached from the value of the body
+/// through blocks, if and match arms and the ones returned with return
+struct TailCallCounter<'a> {
+    name: &'a str,
+    count: usize,
+}
+
+impl<'a> TailCallCounter<'a> {
+    /// count the tail calls of e whose value is the value of the function
+    fn tail(&mut self, e: &Expr) {
+        match e {
+            Expr::Call(call) if try_call_name(call).as_deref() == Some(self.name) => self.count += 1,
+            Expr::Block(b) => self.tail_of_block(&b.block),
+            Expr::If(i) => {
+                self.tail_of_block(&i.then_branch);
+                if let Some((_, else_branch)) = &i.else_branch {
+                    self.tail(else_branch);
+                }
+            }
+            Expr::Match(m) => {
+                for arm in &m.arms {
+                    self.tail(&arm.body);
+                }
+            }
+            Expr::Paren(p) => self.tail(&p.expr),
+            // return values are counted by visit_expr_return
+            _ => {}
+        }
+    }
+
+    fn tail_of_block(&mut self, b: &Block) {
+        if let Some(Stmt::Expr(e)) = b.stmts.last() {
+            self.tail(e);
+        }
+    }
+}
+
+impl<'a, 'ast> Visit<'ast> for TailCallCounter<'a> {
+    fn visit_expr_return(&mut self, e: &'ast ExprReturn) {
+        if let Some(value) = &e.expr {
+            self.tail(value);
+        }
+        visit::visit_expr_return(self, e);
+    }
+
+    // returns inside closures and nested items do not return from the function
+    fn visit_expr_closure(&mut self, _: &'ast ExprClosure) {}
+
+    fn visit_item(&mut self, _: &'ast Item) {}
+}
+
+/// replaces tail calls with an assignment of the loop variables and a jump to the start of the loop
+struct TailCallReplacer<'a> {
+    name: &'a str,
+    loop_variables: &'a [Ident],
+}
+
+impl<'a> TailCallReplacer<'a> {
+    /// all arguments are evaluated before the first loop variable is assigned
+    fn jump(&self, call: &ExprCall) -> Expr {
+        let loop_variables = self.loop_variables;
+        let args = call.args.iter();
+        if loop_variables.len() == 1 {
+            parse_quote!({
+                #(#loop_variables)* = #(#args)*;
+                continue 'tail_call
+            })
+        } else {
+            parse_quote!({
+                (#(#loop_variables),*) = (#(#args),*);
+                continue 'tail_call
+            })
+        }
+    }
+
+    fn as_recursive_call<'e>(&self, e: &'e Expr) -> Option<&'e ExprCall> {
+        match e {
+            Expr::Call(call) if try_call_name(call).as_deref() == Some(self.name) => Some(call),
+            Expr::Paren(p) => self.as_recursive_call(&p.expr),
+            _ => None
+        }
+    }
+}
+
+impl<'a> Fold for TailCallReplacer<'a> {
+    fn fold_expr(&mut self, e: Expr) -> Expr {
+        // return f(..) jumps directly, the return itself would be unreachable
+        if let Expr::Return(ExprReturn{ attrs: _, return_token: _, expr: Some(value) }) = &e {
+            if let Some(call) = self.as_recursive_call(value) {
+                return self.jump(call);
+            }
+        }
+        match e {
+            Expr::Call(call) if try_call_name(&call).as_deref() == Some(self.name) => self.jump(&call),
+            other => fold::fold_expr(self, other)
+        }
+    }
+}
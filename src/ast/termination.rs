@@ -0,0 +1,67 @@
+use std::collections::BTreeSet;
+use quote::quote;
+use syn::{Ident, Stmt, Type, parse_quote};
+use crate::ast::abstract_recursive_match::parse_non_typed_int;
+use crate::ast::coverage::ArgumentRange;
+use crate::ast::optimizable_function::integer_type_range;
+
+/// explains why the original recursion does not terminate for arguments
+/// which are neither a constant nor reached by a branch
+///
+/// all recursive calls move the argument in the same direction by multiples of step_size,
+/// so an argument can only terminate if the recursion reaches constants of its residue class
+pub struct TerminationCheck {
+    /// true if the recursive calls use smaller arguments (n - k)
+    downwards: bool,
+    step_size: u128,
+    constants: BTreeSet<i128>,
+}
+
+impl TerminationCheck {
+    pub fn new(downwards: bool, step_size: u128, constants: BTreeSet<i128>) -> Self {
+        Self { downwards, step_size, constants }
+    }
+
+    /// reason why the recursion diverges for every argument in range
+    pub fn divergence_reason(&self, range: &ArgumentRange) -> String {
+        let m = self.step_size as i128;
+        let residue = range.start.rem_euclid(m);
+        let class: Vec<i128> = self.constants.iter().copied().filter(|c| c.rem_euclid(m) == residue).collect();
+        let (direction, nearest) = if self.downwards {
+            ("smaller", class.first())
+        } else {
+            ("larger", class.last())
+        };
+        match nearest {
+            None => format!(
+                "the recursive calls only use {} arguments n % {} == {}, but no constant has this residue",
+                direction, m, residue),
+            Some(c) if (self.downwards && range.end < *c) || (!self.downwards && range.start > *c) => format!(
+                "the recursive calls only use {} arguments and {} lies beyond the last constant {} in this direction",
+                direction, range, c),
+            Some(_) => format!(
+                "the recursive calls for {} skip the constants and continue with {} arguments until they leave the domain",
+                range, direction)
+        }
+    }
+}
+
+/// panic for arguments below the smallest constant of a recursion which only uses smaller arguments:
+/// the original recursion steps past every constant and leaves the domain of the argument type
+///
+/// None if the argument type can not hold arguments below start
+pub fn below_start_check(parameter: &Ident, arg_type: &Type, start: i128) -> Option<Stmt> {
+    if let Some((min, _)) = integer_type_range(arg_type) {
+        if min >= start {
+            return None;
+        }
+    }
+    let start_lit = parse_non_typed_int(&start);
+    let message = format!("argument {{}} lies below the smallest constant {}, the recursion leaves the domain of {}",
+        start, quote!(#arg_type));
+    Some(parse_quote! {
+        if #parameter < #start_lit {
+            panic!(#message, #parameter);
+        }
+    })
+}
@@ -0,0 +1,180 @@
+use proc_macro_error::abort;
+use syn::{Block, Expr, ExprCall, ExprRange, FnArg, Ident, ItemFn, LitInt, Pat, RangeLimits, ReturnType, Stmt, Type, parse_quote};
+use syn::fold::Fold;
+use crate::ast::abstract_recursive_match::parse_non_typed_int;
+use crate::ast::optimizable_function::{check_receiver, try_get_int_lit};
+use crate::ast::recursive_calls::{collect_calls_in_expr, CallReplacer};
+
+/// a function of two slices whose recursive calls get suffixes of them (edit distance, lcs)
+///
+/// the body is evaluated for every pair of suffixes ordered by their lengths, a row holds the
+/// values of one suffix of the first slice with all suffixes of the second one. all rows are
+/// computed, but only the last ones the calls can read are kept (two if the first slice shrinks
+/// by one element)
+///
+/// example: lcs(a, b) = if a[0] == b[0] { 1 + lcs(&a[1..], &b[1..]) } else { lcs(&a[1..], b).max(lcs(a, &b[1..])) }
+pub struct TwoSequenceFunction {
+    name: String,
+    parameters: (Ident, Ident),
+    parameter_types: (Box<Type>, Box<Type>),
+    return_type: Box<Type>,
+    body: Block,
+    /// the number of rows kept: the largest offset of the first slice plus the current row
+    rows: usize,
+}
+
+/// offsets (k, l) of a recursive call f(&a[k..], &b[l..]), f(a, ..) has the offset 0
+struct SuffixOffsets {
+    first: u128,
+    second: u128,
+}
+
+impl TwoSequenceFunction {
+    /// true if the function has exactly two parameters which are both slices
+    pub fn is_applicable(f: &ItemFn) -> bool {
+        let types: Vec<&Type> = f.sig.inputs.iter().filter_map(|arg| match arg {
+            FnArg::Typed(t) => Some(&*t.ty),
+            FnArg::Receiver(_) => None
+        }).collect();
+        types.len() == 2 && types.iter().all(|ty| is_slice(ty))
+    }
+
+    pub fn create_from(f: &ItemFn) -> Self {
+        let mut parameters = vec![];
+        for arg in &f.sig.inputs {
+            match arg {
+                FnArg::Receiver(r) => check_receiver(r),
+                FnArg::Typed(t) => match &*t.pat {
+                    Pat::Ident(i) => parameters.push((i.ident.clone(), t.ty.clone())),
+                    p => abort!(p, "must be an identifier")
+                }
+            }
+        }
+        let return_type = match &f.sig.output {
+            ReturnType::Default => abort!(f.sig, "macro optimize_recursion needs a return value"),
+            ReturnType::Type(_, t) => t.clone()
+        };
+        let (b, b_type) = parameters.pop().expect("two parameters");
+        let (a, a_type) = parameters.pop().expect("two parameters");
+        let mut result = Self {
+            name: f.sig.ident.to_string(),
+            parameters: (a, b),
+            parameter_types: (a_type, b_type),
+            return_type,
+            body: (*f.block).clone(),
+            rows: 1,
+        };
+        let block = &f.block;
+        let calls = collect_calls_in_expr(&parse_quote!(#block), &result.name);
+        if calls.is_empty() {
+            abort!(f.sig, "need at least 1 recursive call!");
+        }
+        for call in &calls {
+            let offsets = result.offsets(call);
+            result.rows = result.rows.max(offsets.first as usize + 1);
+        }
+        result
+    }
+
+    /// convert the function body to statements filling the rows from the shortest suffixes
+    pub fn construct_stmts(&self) -> Vec<Stmt> {
+        let (a, b) = &self.parameters;
+        let (a_type, b_type) = &self.parameter_types;
+        let return_type = &self.return_type;
+        let rows = self.rows;
+        let body = self.replace_recursive_calls();
+        let block: Block = parse_quote! {
+            {
+                // early returns of the body only leave the evaluation of one pair of suffixes
+                let evaluate = |#a: #a_type, #b: #b_type, rows: &[Vec<#return_type>], row: &[#return_type]| -> #return_type #body;
+                let mut rows: Vec<Vec<#return_type>> = Vec::with_capacity(#rows);
+                for length_a in 0..=#a.len() {
+                    let suffix_a = &#a[#a.len() - length_a..];
+                    let mut row: Vec<#return_type> = Vec::with_capacity(#b.len() + 1);
+                    for length_b in 0..=#b.len() {
+                        let value = evaluate(suffix_a, &#b[#b.len() - length_b..], &rows, &row);
+                        row.push(value);
+                    }
+                    if rows.len() < #rows {
+                        rows.push(row);
+                    } else {
+                        rows[length_a % #rows] = row;
+                    }
+                }
+                rows[#a.len() % #rows][#b.len()].clone()
+            }
+        };
+        block.stmts
+    }
+
+    /// rewrite the body: a call reads the row of the first suffix at the length of the second suffix
+    ///
+    /// the lengths are taken from the sub-slices, so an out of range offset panics like the recursion does
+    fn replace_recursive_calls(&self) -> Block {
+        let (a, b) = &self.parameters;
+        let rows = self.rows;
+        let mut replacer = CallReplacer::new(&self.name, |call: ExprCall| {
+            let offsets = self.offsets(&call);
+            let length_b: Expr = match offsets.second {
+                0 => parse_quote!(#b.len()),
+                l => {
+                    let l = offset_lit(l);
+                    parse_quote!(#b[#l..].len())
+                }
+            };
+            match offsets.first {
+                0 => parse_quote!(row[#length_b].clone()),
+                k => {
+                    let k = offset_lit(k);
+                    parse_quote!(rows[#a[#k..].len() % #rows][#length_b].clone())
+                }
+            }
+        });
+        replacer.fold_block(self.body.clone())
+    }
+
+    fn offsets(&self, call: &ExprCall) -> SuffixOffsets {
+        let (a, b) = &self.parameters;
+        if call.args.len() != 2 {
+            abort!(call, "recursive call needs 2 arguments");
+        }
+        let first = suffix_offset(&call.args[0], a);
+        let second = suffix_offset(&call.args[1], b);
+        if first == 0 && second == 0 {
+            abort!(call, "the recursive call does not shrink {} or {} and does not terminate", a, b;
+                help = "recursive calls need the form {}(&{}[k..], &{}[l..]) with k > 0 or l > 0", self.name, a, b);
+        }
+        SuffixOffsets { first, second }
+    }
+}
+
+/// &[T]
+fn is_slice(ty: &Type) -> bool {
+    matches!(ty, Type::Reference(r) if r.mutability.is_none() && matches!(&*r.elem, Type::Slice(_)))
+}
+
+/// offset k of a recursive call argument &s[k..], 0 for s itself
+fn suffix_offset(arg: &Expr, parameter: &Ident) -> u128 {
+    let is_parameter = |e: &Expr| matches!(e, Expr::Path(p) if p.path.is_ident(parameter));
+    let offset = match arg {
+        e if is_parameter(e) => Some(0),
+        Expr::Reference(r) if r.mutability.is_none() => match &*r.expr {
+            Expr::Index(i) if is_parameter(&i.expr) => match &*i.index {
+                Expr::Range(ExprRange{ attrs: _, from: Some(from), limits: RangeLimits::HalfOpen(_), to: None }) => {
+                    try_get_int_lit(from).and_then(|k| k.base10_parse::<u128>().ok())
+                }
+                _ => None
+            },
+            _ => None
+        },
+        _ => None
+    };
+    match offset {
+        Some(offset) => offset,
+        None => abort!(arg, "the argument needs the form {} or &{}[k..] with a constant k", parameter, parameter)
+    }
+}
+
+fn offset_lit(offset: u128) -> LitInt {
+    parse_non_typed_int(&(offset as i128))
+}
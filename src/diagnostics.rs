@@ -0,0 +1,84 @@
+use std::cell::RefCell;
+use proc_macro_error::{abort_if_dirty, emit_error};
+use quote::{quote_spanned, ToTokens};
+use syn::{ItemFn, LitStr, Stmt};
+use proc_macro2::Span;
+
+/// warnings of the current expansion
+///
+/// proc_macro_error only shows warnings on nightly, so every warning is turned into the use of
+/// a deprecated constant at the span of the warning, which rustc reports on stable as well
+struct Warning {
+    span: Span,
+    message: String,
+    /// informational notes (the chosen strategy, the first overflowing argument) are only
+    /// emitted with option `verbose` and stay warnings with option `strict`
+    report: bool,
+}
+
+thread_local! {
+    static WARNINGS: RefCell<Vec<Warning>> = const { RefCell::new(Vec::new()) };
+}
+
+/// warn about a problem at the first token of target (compile error with option `strict`)
+macro_rules! warning {
+    ($target:expr, $($message:tt)*) => {
+        $crate::diagnostics::push_warning($crate::diagnostics::first_span(&$target), format!($($message)*), false)
+    };
+}
+
+/// report a decision or finding of the macro, e.g. the strategy chosen by `strategy = "auto"`
+/// (only shown with option `verbose`)
+macro_rules! report {
+    (at $target:expr, $($message:tt)*) => {
+        $crate::diagnostics::push_warning($crate::diagnostics::first_span(&$target), format!($($message)*), true)
+    };
+    ($($message:tt)*) => {
+        $crate::diagnostics::push_warning(proc_macro2::Span::call_site(), format!($($message)*), true)
+    };
+}
+
+pub(crate) use {report, warning};
+
+/// the span of the first token of target (joining spans needs nightly)
+pub fn first_span<T: ToTokens>(target: &T) -> Span {
+    target.to_token_stream().into_iter().next()
+        .map_or_else(Span::call_site, |token| token.span())
+}
+
+pub fn push_warning(span: Span, message: String, report: bool) {
+    WARNINGS.with(|warnings| warnings.borrow_mut().push(Warning { span, message, report }));
+}
+
+/// forget the warnings of an earlier expansion which aborted
+pub fn reset() {
+    WARNINGS.with(|warnings| warnings.borrow_mut().clear());
+}
+
+/// abort with every problem as an error (option `strict`)
+pub fn abort_on_warnings() {
+    WARNINGS.with(|warnings| {
+        for warning in warnings.borrow().iter().filter(|w| !w.report) {
+            emit_error!(warning.span, "{}", warning.message);
+        }
+    });
+    abort_if_dirty();
+}
+
+/// prepend the warnings as uses of deprecated constants to the body of f,
+/// the reports only if they are asked for (option `verbose`)
+pub fn emit_into(f: &mut ItemFn, verbose: bool) {
+    let warnings = WARNINGS.with(|warnings| warnings.take());
+    for Warning { span, message, report: _ } in warnings.into_iter().rev().filter(|w| verbose || !w.report) {
+        let note = LitStr::new(&message, span);
+        let stmt: Stmt = syn::parse2(quote_spanned!{span=>
+            {
+                #[deprecated(note = #note)]
+                #[allow(non_upper_case_globals)]
+                const optimize_recursion_warning: u8 = 0;
+                let _ = optimize_recursion_warning;
+            }
+        }).expect("warning statement");
+        f.block.stmts.insert(0, stmt);
+    }
+}
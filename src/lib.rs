@@ -1,18 +1,46 @@
 mod ast;
+mod diagnostics;
+mod options;
 
-use proc_macro;
-use proc_macro_error::proc_macro_error;
+use proc_macro_error::{abort, proc_macro_error};
 use quote::quote;
-use syn;
-use syn::parse_macro_input;
-use crate::ast::OptimizableFunction;
+use syn::{parse_macro_input, AttributeArgs};
+use crate::ast::{MutualRecursion, OptimizableFunction};
+use crate::options::OptimizeOptions;
 
 #[proc_macro_error]
 #[proc_macro_attribute]
-pub fn optimize_recursion(_attr: proc_macro::TokenStream, tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let input_function: syn::ItemFn = parse_macro_input!(tokens as syn::ItemFn);
-    let function = OptimizableFunction::from(input_function.clone());
-    let optimized_result = function.optimize();
+pub fn optimize_recursion(attr: proc_macro::TokenStream, tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    diagnostics::reset();
+    let args = parse_macro_input!(attr as AttributeArgs);
+    let input_function = match parse_macro_input!(tokens as syn::Item) {
+        syn::Item::Fn(f) => f,
+        // functions of a module can call each other
+        syn::Item::Mod(m) => {
+            if let Some(arg) = args.first() {
+                abort!(arg, "options are not supported for a module");
+            }
+            let optimized_result = MutualRecursion::create_from(&m).optimize();
+            return quote!(#optimized_result).into();
+        }
+        item => abort!(item, "optimize_recursion can only be used on a function or a module")
+    };
+    let options = OptimizeOptions::from_args(args);
+    let strict = options.strict;
+    let verbose = options.verbose;
+    if let Some(mut optimized_result) = OptimizableFunction::optimize_nested(&input_function, &options) {
+        if strict {
+            diagnostics::abort_on_warnings();
+        }
+        diagnostics::emit_into(&mut optimized_result, verbose);
+        return quote!(#optimized_result).into();
+    }
+    let function = OptimizableFunction::new(input_function.clone(), options);
+    let mut optimized_result = function.optimize();
+    if strict {
+        diagnostics::abort_on_warnings();
+    }
+    diagnostics::emit_into(&mut optimized_result, verbose);
     let result: proc_macro::TokenStream = quote!(#optimized_result).into();
     println!("result function: {}", result);
     return result;
@@ -20,4 +48,4 @@ pub fn optimize_recursion(_attr: proc_macro::TokenStream, tokens: proc_macro::To
     // TODO: use result
     // let result = quote!(#input_function).into();
     // result
-}
\ No newline at end of file
+}
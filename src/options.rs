@@ -0,0 +1,117 @@
+use proc_macro_error::abort;
+use syn::{AttributeArgs, Expr, ExprRange, Ident, Lit, LitInt, LitStr, Meta, MetaList, MetaNameValue, NestedMeta, RangeLimits, Token, parse_quote};
+use proc_macro2::Span;
+use syn::parse::Parser;
+use syn::punctuated::Punctuated;
+
+/// options given to the macro attribute, e.g. `#[optimize_recursion(total)]`
+#[derive(Debug, Clone, Default)]
+pub struct OptimizeOptions {
+    /// abort compilation if some arguments of the parameter type are not covered
+    pub total: bool,
+    /// panic with a precise message before computing a result which overflows the return type
+    pub overflow_check: bool,
+    /// abort compilation instead of warning about a problem of the function
+    pub strict: bool,
+    /// report the chosen strategy and the first overflowing argument in compile warnings
+    pub verbose: bool,
+    /// extend the function below the smallest start constant by inverting a linear recursive formula
+    pub backwards: bool,
+    /// how the recursion is replaced, e.g. `strategy = "auto"`
+    pub strategy: Strategy,
+    /// the values a secondary parameter can take, e.g. `domain(state = "State::A, State::B")`
+    /// or `domain(digit = "0..10")`
+    pub domains: Vec<(Ident, Vec<Expr>)>,
+}
+
+/// transformation used to replace the recursion
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum Strategy {
+    /// loop over the eureka tuple (divide and conquer recursions use a sorted memo),
+    /// abort if the function does not fit
+    #[default]
+    Iterative,
+    /// the iterative transform if the function fits, a memoized evaluation otherwise
+    Auto,
+    /// keep the body as it is and evaluate it with an explicit stack of continuation frames
+    Stack,
+    /// fill a table with every value up to the argument, recursive calls may use any smaller argument
+    Table,
+}
+
+impl OptimizeOptions {
+    pub fn from_args(args: AttributeArgs) -> Self {
+        let mut options = Self::default();
+        for arg in args {
+            match &arg {
+                NestedMeta::Meta(Meta::Path(p)) if p.is_ident("total") => {
+                    options.total = true;
+                }
+                NestedMeta::Meta(Meta::Path(p)) if p.is_ident("overflow_check") => {
+                    options.overflow_check = true;
+                }
+                NestedMeta::Meta(Meta::Path(p)) if p.is_ident("strict") => {
+                    options.strict = true;
+                }
+                NestedMeta::Meta(Meta::Path(p)) if p.is_ident("verbose") => {
+                    options.verbose = true;
+                }
+                NestedMeta::Meta(Meta::Path(p)) if p.is_ident("backwards") => {
+                    options.backwards = true;
+                }
+                NestedMeta::Meta(Meta::NameValue(MetaNameValue{ path, eq_token: _, lit })) if path.is_ident("strategy") => {
+                    options.strategy = match lit {
+                        Lit::Str(s) if s.value() == "auto" => Strategy::Auto,
+                        Lit::Str(s) if s.value() == "iterative" => Strategy::Iterative,
+                        Lit::Str(s) if s.value() == "stack" => Strategy::Stack,
+                        Lit::Str(s) if s.value() == "table" => Strategy::Table,
+                        _ => abort!(lit, "unknown strategy, expected \"auto\", \"iterative\", \"stack\" or \"table\"")
+                    };
+                }
+                NestedMeta::Meta(Meta::List(MetaList{ path, paren_token: _, nested })) if path.is_ident("domain") => {
+                    for domain in nested {
+                        match domain {
+                            NestedMeta::Meta(Meta::NameValue(MetaNameValue{ path, eq_token: _, lit: Lit::Str(values) })) => {
+                                match path.get_ident() {
+                                    Some(parameter) => options.domains.push((parameter.clone(), parse_domain(values))),
+                                    None => abort!(path, "expected a parameter name")
+                                }
+                            }
+                            _ => abort!(domain, "expected a domain of the form parameter = \"values\"")
+                        }
+                    }
+                }
+                _ => abort!(arg, "unknown option for optimize_recursion")
+            }
+        }
+        options
+    }
+}
+
+/// the values of a domain: a range of integer literals or a comma separated list of values
+fn parse_domain(values: &LitStr) -> Vec<Expr> {
+    if let Ok(Expr::Range(ExprRange{ attrs: _, from: Some(from), limits, to: Some(to) })) = values.parse::<Expr>() {
+        let bound = |e: &Expr| match e {
+            Expr::Lit(syn::ExprLit{ attrs: _, lit: Lit::Int(i) }) => i.base10_parse::<i128>().ok(),
+            _ => None
+        };
+        if let (Some(from), Some(to)) = (bound(&from), bound(&to)) {
+            let to = match limits {
+                RangeLimits::HalfOpen(_) => to,
+                RangeLimits::Closed(_) => to + 1
+            };
+            return (from..to).map(|v| {
+                let literal = LitInt::new(&v.unsigned_abs().to_string(), Span::call_site());
+                if v < 0 {
+                    parse_quote!(-#literal)
+                } else {
+                    parse_quote!(#literal)
+                }
+            }).collect();
+        }
+    }
+    match Punctuated::<Expr, Token![,]>::parse_terminated.parse_str(&values.value()) {
+        Ok(values) if !values.is_empty() => values.into_iter().collect(),
+        _ => abort!(values, "expected a range like \"0..10\" or a list of values like \"State::A, State::B\"")
+    }
+}
@@ -0,0 +1,137 @@
+use optimize_recursion::optimize_recursion;
+
+// every recursive call is a tail call, computed in a loop
+#[optimize_recursion(strategy = "auto")]
+pub fn ifelse(n: u64) -> u64 {
+    match n {
+        0 => 0,
+        _ => if n.is_multiple_of(3) { ifelse(n - 3) } else { ifelse(n - 1) }
+    }
+}
+
+pub fn ifelse_base(n: u64) -> u64 {
+    match n {
+        0 => 0,
+        _ => if n.is_multiple_of(3) { ifelse_base(n - 3) } else { ifelse_base(n - 1) }
+    }
+}
+
+// no eureka tuple: the step depends on the argument, memoized in a dense Vec
+#[optimize_recursion(strategy = "auto")]
+pub fn thirds(n: u64) -> u64 {
+    match n {
+        0 => 0,
+        _ => if n.is_multiple_of(3) { thirds(n - 3) + 1 } else { thirds(n - 1) }
+    }
+}
+
+// memoized in a dense Vec up to a size limit, huge arguments use the HashMap of the memo
+#[optimize_recursion(strategy = "auto")]
+pub fn capped_thirds(n: u64) -> u64 {
+    match n {
+        0 => 0,
+        _ => if n > u64::MAX - 10 { capped_thirds(n - 20) + 1 } else if n.is_multiple_of(3) { capped_thirds(n - 3) + 1 } else { n % 7 }
+    }
+}
+
+pub fn thirds_base(n: u64) -> u64 {
+    match n {
+        0 => 0,
+        _ => if n.is_multiple_of(3) { thirds_base(n - 3) + 1 } else { thirds_base(n - 1) }
+    }
+}
+
+// halving steps, memoized in a sorted Vec
+#[optimize_recursion(strategy = "auto")]
+pub fn collatz_steps(n: u64) -> u64 {
+    match n {
+        1 => 0,
+        _ => 1 + if n.is_multiple_of(2) { collatz_steps(n / 2) } else { collatz_steps(3 * n + 1) }
+    }
+}
+
+pub fn collatz_steps_base(n: u64) -> u64 {
+    match n {
+        1 => 0,
+        _ => 1 + if n.is_multiple_of(2) { collatz_steps_base(n / 2) } else { collatz_steps_base(3 * n + 1) }
+    }
+}
+
+// fits the iterative transform
+#[optimize_recursion(strategy = "auto")]
+pub fn fib(n: u32) -> u64 {
+    match n {
+        0 => 0,
+        1 => 1,
+        _ => fib(n - 1) + fib(n - 2)
+    }
+}
+
+// signed argument moving in both directions, memoized in a HashMap
+#[optimize_recursion(strategy = "auto")]
+pub fn mixed_directions(n: i32) -> i32 {
+    match n {
+        0 => 0,
+        10 => 10,
+        _ => mixed_directions(n - 1) + mixed_directions(n + 1)
+    }
+}
+
+// the match is on the second argument, the memo keys both
+#[optimize_recursion(strategy = "auto")]
+pub fn power(base: u64, exponent: u32) -> u64 {
+    match exponent {
+        0 => 1,
+        _ => base * power(base, exponent - 1)
+    }
+}
+
+#[test]
+fn test_ifelse() {
+    for i in 0..200 {
+        assert_eq!(ifelse(i), ifelse_base(i));
+    }
+    assert_eq!(ifelse(1_000_000), 0);
+}
+
+#[test]
+fn test_thirds() {
+    for i in 0..200 {
+        assert_eq!(thirds(i), thirds_base(i));
+    }
+    assert_eq!(thirds(300_000), 100_000);
+}
+
+#[test]
+fn test_capped_thirds_huge_argument() {
+    assert_eq!(capped_thirds(u64::MAX), 1 + (u64::MAX - 20) % 7);
+    assert_eq!(capped_thirds(9), 3);
+}
+
+#[test]
+fn test_collatz_steps() {
+    for i in 1..1000 {
+        assert_eq!(collatz_steps(i), collatz_steps_base(i));
+    }
+    assert_eq!(collatz_steps(27), 111);
+}
+
+#[test]
+fn test_fib() {
+    assert_eq!(fib(0), 0);
+    assert_eq!(fib(10), 55);
+    assert_eq!(fib(93), 12200160415121876738);
+}
+
+#[test]
+#[should_panic(expected = "recursion does not terminate for argument")]
+fn test_mixed_directions() {
+    mixed_directions(5);
+}
+
+#[test]
+fn test_power() {
+    assert_eq!(power(0, 0), 1);
+    assert_eq!(power(3, 4), 81);
+    assert_eq!(power(2, 63), 1 << 63);
+}
@@ -0,0 +1,59 @@
+use optimize_recursion::optimize_recursion;
+
+// negafibonacci: fib(n - 2) = fib(n) - fib(n - 1)
+#[optimize_recursion(backwards)]
+pub fn nega_fib(n: i64) -> i64 {
+    match n {
+        0 => 0,
+        1 => 1,
+        _ => nega_fib(n - 1) + nega_fib(n - 2)
+    }
+}
+
+#[optimize_recursion(backwards)]
+pub fn alternating(n: i64) -> i64 {
+    match n {
+        0 => 0,
+        1 => 1,
+        _ => alternating(n - 1) - alternating(n - 2)
+    }
+}
+
+#[optimize_recursion(backwards)]
+pub fn linear(n: i32) -> i64 {
+    match n {
+        0 => 0,
+        _ => linear(n - 1) + 2
+    }
+}
+
+pub fn alternating_base(n: i64) -> i64 {
+    match n {
+        0 => 0,
+        1 => 1,
+        _ if n > 1 => alternating_base(n - 1) - alternating_base(n - 2),
+        _ => alternating_base(n + 1) - alternating_base(n + 2)
+    }
+}
+
+#[test]
+pub fn test_nega_fib() {
+    assert_eq!(nega_fib(10), 55);
+    assert_eq!(nega_fib(-1), 1);
+    assert_eq!(nega_fib(-2), -1);
+    assert_eq!(nega_fib(-3), 2);
+    assert_eq!(nega_fib(-10), -55);
+}
+
+#[test]
+pub fn test_alternating() {
+    for i in -20..20 {
+        assert_eq!(alternating(i), alternating_base(i));
+    }
+}
+
+#[test]
+pub fn test_linear() {
+    assert_eq!(linear(5), 10);
+    assert_eq!(linear(-3), -6);
+}
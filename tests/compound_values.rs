@@ -0,0 +1,122 @@
+use optimize_recursion::optimize_recursion;
+
+// the helper carries the last two values, the function projects the first one
+#[optimize_recursion]
+pub fn foo_incremental(n: i64) -> i64 {
+    fn foo(n: i64) -> (i64, i64) {
+        match n {
+            0 => (0, 1000), // second argument is not relevant
+            1 => (1, 0),
+            _ => {
+                let r = foo(n - 1);
+                (r.0 - r.1, r.0)
+            }
+        }
+    }
+    foo(n).0
+}
+
+pub fn foo_base(n: i64) -> i64 {
+    match n {
+        0 => 0,
+        1 => 1,
+        _ => foo_base(n - 1) - foo_base(n - 2)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Fibonacci {
+    current: u64,
+    next: u64,
+}
+
+#[optimize_recursion]
+pub fn fibonacci_pair(n: u32) -> Fibonacci {
+    match n {
+        0 => Fibonacci { current: 0, next: 1 },
+        _ => Fibonacci {
+            current: fibonacci_pair(n - 1).next,
+            next: fibonacci_pair(n - 1).current + fibonacci_pair(n - 1).next
+        }
+    }
+}
+
+pub fn fibonacci_pair_base(n: u32) -> Fibonacci {
+    match n {
+        0 => Fibonacci { current: 0, next: 1 },
+        _ => {
+            let previous = fibonacci_pair_base(n - 1);
+            Fibonacci { current: previous.next, next: previous.current + previous.next }
+        }
+    }
+}
+
+// the helper returns a struct, the function projects a named field
+#[optimize_recursion]
+pub fn fibonacci_projected(n: u32) -> u64 {
+    fn pair(n: u32) -> Fibonacci {
+        match n {
+            0 => Fibonacci { current: 0, next: 1 },
+            _ => {
+                let p = pair(n - 1);
+                Fibonacci { current: p.next, next: p.current + p.next }
+            }
+        }
+    }
+    pair(n).current
+}
+
+// number of ways to climb n stairs with steps of 1 or 2 and the number of steps of all ways together
+#[optimize_recursion]
+pub fn stairs(n: u64) -> (u64, u64) {
+    match n {
+        0 => (1, 0),
+        1 => (1, 1),
+        _ => (
+            stairs(n - 1).0 + stairs(n - 2).0,
+            stairs(n - 1).1 + stairs(n - 1).0 + stairs(n - 2).1 + stairs(n - 2).0
+        )
+    }
+}
+
+pub fn stairs_base(n: u64) -> (u64, u64) {
+    match n {
+        0 => (1, 0),
+        1 => (1, 1),
+        _ => {
+            let (one, two) = (stairs_base(n - 1), stairs_base(n - 2));
+            (one.0 + two.0, one.1 + one.0 + two.1 + two.0)
+        }
+    }
+}
+
+#[test]
+fn projected_tuple() {
+    for n in 0..30 {
+        assert_eq!(foo_incremental(n), foo_base(n));
+    }
+    assert_eq!(foo_incremental(1_000_000), foo_base(1_000_000 % 6));
+}
+
+#[test]
+fn struct_value() {
+    for n in 0..90 {
+        assert_eq!(fibonacci_pair(n), fibonacci_pair_base(n));
+    }
+    assert_eq!(fibonacci_pair(90).current, 2880067194370816120);
+}
+
+#[test]
+fn projected_struct() {
+    for n in 0..90 {
+        assert_eq!(fibonacci_projected(n), fibonacci_pair_base(n).current);
+    }
+}
+
+#[test]
+fn tuple_value() {
+    for n in 0..25 {
+        assert_eq!(stairs(n), stairs_base(n));
+    }
+    assert_eq!(stairs(3), (3, 7));
+}
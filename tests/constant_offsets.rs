@@ -0,0 +1,106 @@
+use optimize_recursion::optimize_recursion;
+
+// ordered ways to pay n with the coins
+#[optimize_recursion]
+pub fn ways(n: u64) -> u64 {
+    const COINS: [u64; 4] = [1, 2, 5, 10];
+    match n {
+        0 => 1,
+        _ => COINS.iter().map(|&c| if n >= c { ways(n - c) } else { 0 }).sum()
+    }
+}
+
+pub fn ways_base(n: u64) -> u64 {
+    const COINS: [u64; 4] = [1, 2, 5, 10];
+    match n {
+        0 => 1,
+        _ => COINS.iter().map(|&c| if n >= c { ways_base(n - c) } else { 0 }).sum()
+    }
+}
+
+// sequences of dice throws summing up to n
+#[optimize_recursion]
+pub fn dice_sums(n: u32) -> u64 {
+    match n {
+        0 => 1,
+        _ => [1, 2, 3, 4, 5, 6].into_iter().map(|d| if n >= d { dice_sums(n - d) } else { 0 }).sum()
+    }
+}
+
+pub fn dice_sums_base(n: u32) -> u64 {
+    match n {
+        0 => 1,
+        _ => (1..=6).map(|d| if n >= d { dice_sums_base(n - d) } else { 0 }).sum()
+    }
+}
+
+#[optimize_recursion]
+pub fn every_third(n: u64) -> u64 {
+    const STEP: u64 = 3;
+    match n {
+        0 => 1,
+        1 => 2,
+        2 => 3,
+        _ => every_third(n - STEP) * 2
+    }
+}
+
+// the block declares its own STEP, only the step of the call is the outer one
+#[optimize_recursion(strategy = "table")]
+pub fn shadowed_step(n: u64) -> u64 {
+    const STEP: u64 = 1;
+    match n {
+        0 => 1,
+        _ => shadowed_step(n - STEP) + {
+            const STEP: u64 = 10;
+            STEP
+        }
+    }
+}
+
+pub fn shadowed_step_base(n: u64) -> u64 {
+    const STEP: u64 = 1;
+    match n {
+        0 => 1,
+        _ => shadowed_step_base(n - STEP) + {
+            const STEP: u64 = 10;
+            STEP
+        }
+    }
+}
+
+#[test]
+fn coin_change() {
+    for i in 0..30 {
+        assert_eq!(ways(i), ways_base(i));
+    }
+    assert_eq!(ways(0), 1);
+    assert_eq!(ways(3), 3);
+    let mut table = vec![1u64];
+    for n in 1..=80 {
+        table.push([1, 2, 5, 10].iter().filter(|&&c| n >= c).map(|&c| table[(n - c) as usize]).sum());
+    }
+    assert_eq!(ways(80), table[80]);
+}
+
+#[test]
+fn dice() {
+    for i in 0..25 {
+        assert_eq!(dice_sums(i), dice_sums_base(i));
+    }
+}
+
+#[test]
+fn const_step() {
+    assert_eq!(every_third(0), 1);
+    assert_eq!(every_third(4), 4);
+    assert_eq!(every_third(8), 12);
+    assert_eq!(every_third(9), 8);
+}
+
+#[test]
+fn shadowed_const() {
+    for i in 0..20 {
+        assert_eq!(shadowed_step(i), shadowed_step_base(i));
+    }
+}
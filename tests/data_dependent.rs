@@ -0,0 +1,76 @@
+use optimize_recursion::optimize_recursion;
+
+// hofstadter q sequence
+#[optimize_recursion]
+pub fn q(n: u64) -> u64 {
+    match n {
+        1 => 1,
+        2 => 1,
+        _ => q(n - q(n - 1)) + q(n - q(n - 2))
+    }
+}
+
+pub fn q_base(n: u64) -> u64 {
+    match n {
+        1 => 1,
+        2 => 1,
+        _ => q_base(n - q_base(n - 1)) + q_base(n - q_base(n - 2))
+    }
+}
+
+// golomb's sequence: g(n) is the number of times n occurs in the sequence
+#[optimize_recursion]
+pub fn golomb(n: u32) -> u32 {
+    match n {
+        1 => 1,
+        _ => 1 + golomb(n - golomb(golomb(n - 1)))
+    }
+}
+
+// the table also works for fixed steps
+#[optimize_recursion(strategy = "table")]
+pub fn fib(n: u32) -> u64 {
+    match n {
+        0 => 0,
+        1 => 1,
+        _ => fib(n - 1) + fib(n - 2)
+    }
+}
+
+// needs its own argument
+#[optimize_recursion]
+pub fn cyclic(n: u32) -> u32 {
+    match n {
+        0 => 0,
+        _ => cyclic(n + n - 1) + 1
+    }
+}
+
+#[test]
+fn test_q() {
+    for n in 1..25 {
+        assert_eq!(q(n), q_base(n));
+    }
+    assert_eq!(q(100_000), 48_157);
+}
+
+#[test]
+fn test_golomb() {
+    let expected = [1, 2, 2, 3, 3, 4, 4, 4, 5, 5, 5, 6];
+    for (i, g) in expected.iter().enumerate() {
+        assert_eq!(golomb(i as u32 + 1), *g);
+    }
+}
+
+#[test]
+fn test_fib() {
+    assert_eq!(fib(0), 0);
+    assert_eq!(fib(1), 1);
+    assert_eq!(fib(50), 12_586_269_025);
+}
+
+#[test]
+#[should_panic(expected = "cyclic(1) needs cyclic(1) which is not computed yet")]
+fn test_cyclic() {
+    cyclic(3);
+}
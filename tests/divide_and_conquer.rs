@@ -0,0 +1,61 @@
+use optimize_recursion::optimize_recursion;
+
+#[optimize_recursion]
+pub fn halves_and_thirds(n: u64) -> u64 {
+    match n {
+        0 => 1,
+        _ => halves_and_thirds(n / 2) + halves_and_thirds(n / 3)
+    }
+}
+
+pub fn halves_and_thirds_base(n: u64) -> u64 {
+    match n {
+        0 => 1,
+        _ => halves_and_thirds_base(n / 2) + halves_and_thirds_base(n / 3)
+    }
+}
+
+#[optimize_recursion]
+pub fn bit_count(n: u32) -> u32 {
+    match n {
+        0 => 0,
+        _ => bit_count(n >> 1) + (n & 1)
+    }
+}
+
+// exponentiation by squaring
+#[optimize_recursion]
+pub fn two_power(n: u32) -> u128 {
+    match n {
+        0 => 1,
+        _ => if n.is_multiple_of(2) {
+            let half = two_power(n / 2);
+            half * half
+        } else {
+            2 * two_power(n - 1)
+        }
+    }
+}
+
+#[test]
+pub fn test_halves_and_thirds() {
+    for i in 0..1000 {
+        assert_eq!(halves_and_thirds(i), halves_and_thirds_base(i));
+    }
+    assert_eq!(halves_and_thirds(1_000_000_000_000), halves_and_thirds_base(1_000_000_000_000));
+}
+
+#[test]
+pub fn test_bit_count() {
+    for i in 0..1000u32 {
+        assert_eq!(bit_count(i), i.count_ones());
+    }
+    assert_eq!(bit_count(u32::MAX), 32);
+}
+
+#[test]
+pub fn test_two_power() {
+    for i in 0..128 {
+        assert_eq!(two_power(i), 1 << i);
+    }
+}
@@ -0,0 +1,88 @@
+use optimize_recursion::optimize_recursion;
+
+// binary strings of length n without two consecutive ones
+#[optimize_recursion]
+pub fn strings(n: u64, last_was_one: bool) -> u64 {
+    match n {
+        0 => 1,
+        _ => strings(n - 1, false) + if last_was_one { 0 } else { strings(n - 1, true) }
+    }
+}
+
+pub fn strings_base(n: u64, last_was_one: bool) -> u64 {
+    match n {
+        0 => 1,
+        _ => strings_base(n - 1, false) + if last_was_one { 0 } else { strings_base(n - 1, true) }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Light {
+    Red,
+    Yellow,
+    Green,
+}
+
+// sequences of n lights which only switch to the next color or stay, starting with the given color
+#[optimize_recursion(domain(light = "Light::Red, Light::Yellow, Light::Green"))]
+pub fn sequences(light: Light, n: u32) -> u64 {
+    match n {
+        1 => 1,
+        _ => sequences(light, n - 1) + match light {
+            Light::Red => sequences(Light::Green, n - 1),
+            Light::Yellow => sequences(Light::Red, n - 1),
+            Light::Green => sequences(Light::Yellow, n - 1)
+        }
+    }
+}
+
+// numbers with n digits whose neighbouring digits differ by at most 1, starting with digit
+#[optimize_recursion(domain(digit = "0..10"))]
+pub fn smooth(n: u64, digit: u8) -> u64 {
+    match n {
+        1 => 1,
+        _ => (if digit > 0 { smooth(n - 1, digit - 1) } else { 0 })
+            + smooth(n - 1, digit)
+            + if digit < 9 { smooth(n - 1, digit + 1) } else { 0 }
+    }
+}
+
+pub fn smooth_base(n: u64, digit: u8) -> u64 {
+    match n {
+        1 => 1,
+        _ => (if digit > 0 { smooth_base(n - 1, digit - 1) } else { 0 })
+            + smooth_base(n - 1, digit)
+            + if digit < 9 { smooth_base(n - 1, digit + 1) } else { 0 }
+    }
+}
+
+#[test]
+fn flag() {
+    for n in 0..20 {
+        assert_eq!(strings(n, false), strings_base(n, false));
+        assert_eq!(strings(n, true), strings_base(n, true));
+    }
+    assert_eq!(strings(90, false), 7540113804746346429);
+}
+
+#[test]
+fn enum_state() {
+    assert_eq!(sequences(Light::Red, 1), 1);
+    assert_eq!(sequences(Light::Yellow, 3), 4);
+    assert_eq!(sequences(Light::Green, 60), 1 << 59);
+}
+
+#[test]
+fn declared_range() {
+    for n in 1..10 {
+        for digit in 0..10 {
+            assert_eq!(smooth(n, digit), smooth_base(n, digit));
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "digit is outside of the declared domain")]
+fn outside_of_domain() {
+    smooth(5, 10);
+}
@@ -0,0 +1,86 @@
+use optimize_recursion::optimize_recursion;
+
+#[optimize_recursion]
+pub fn fib(n: u64) -> u64 {
+    if n < 2 {
+        return n;
+    }
+    fib(n - 1) + fib(n - 2)
+}
+
+#[optimize_recursion]
+pub fn tribonacci(n: u32) -> u64 {
+    if n == 0 {
+        0
+    } else if n == 1 || n == 2 {
+        1
+    } else {
+        tribonacci(n - 1) + tribonacci(n - 2) + tribonacci(n - 3)
+    }
+}
+
+// the guards come before an if of the formula
+#[optimize_recursion]
+pub fn alternating(n: u64) -> u64 {
+    if n == 0 {
+        return 1;
+    }
+    if 2 > n {
+        return 2;
+    }
+    if n.is_multiple_of(2) {
+        alternating(n - 1) + alternating(n - 2)
+    } else {
+        alternating(n - 1) * 2
+    }
+}
+
+pub fn alternating_base(n: u64) -> u64 {
+    match n {
+        0 => 1,
+        1 => 2,
+        _ if n.is_multiple_of(2) => alternating_base(n - 1) + alternating_base(n - 2),
+        _ => alternating_base(n - 1) * 2
+    }
+}
+
+// guards on the length of a slice
+#[optimize_recursion]
+pub fn decode_ways(s: &[u8]) -> u64 {
+    if s.len() == 0 {
+        return 1;
+    }
+    if s[0] == b'0' {
+        return 0;
+    }
+    decode_ways(&s[1..]) + if s.len() >= 2 && (s[0] - b'0') * 10 + s[1] - b'0' <= 26 { decode_ways(&s[2..]) } else { 0 }
+}
+
+#[test]
+fn guard_clause() {
+    assert_eq!(fib(0), 0);
+    assert_eq!(fib(1), 1);
+    assert_eq!(fib(90), 2880067194370816120);
+}
+
+#[test]
+fn if_chain() {
+    let expected = [0, 1, 1, 2, 4, 7, 13, 24, 44, 81];
+    for (n, value) in expected.iter().enumerate() {
+        assert_eq!(tribonacci(n as u32), *value);
+    }
+}
+
+#[test]
+fn formula_with_if() {
+    for n in 0..40 {
+        assert_eq!(alternating(n), alternating_base(n));
+    }
+}
+
+#[test]
+fn slice_length() {
+    assert_eq!(decode_ways(b"226"), 3);
+    assert_eq!(decode_ways(b"06"), 0);
+    assert_eq!(decode_ways(b"11106"), 2);
+}
@@ -0,0 +1,92 @@
+use optimize_recursion::optimize_recursion;
+
+#[optimize_recursion]
+pub fn catalan(n: usize) -> u64 {
+    match n {
+        0 => 1,
+        _ => (0..n).map(|i| catalan(i) * catalan(n - 1 - i)).sum()
+    }
+}
+
+pub fn catalan_base(n: usize) -> u64 {
+    match n {
+        0 => 1,
+        _ => (0..n).map(|i| catalan_base(i) * catalan_base(n - 1 - i)).sum()
+    }
+}
+
+// the same recurrence written with a for loop
+#[optimize_recursion]
+pub fn catalan_loop(n: u32) -> u64 {
+    match n {
+        0 => 1,
+        _ => {
+            let mut sum = 0;
+            for i in 0..n {
+                sum += catalan_loop(i) * catalan_loop(n - 1 - i);
+            }
+            sum
+        }
+    }
+}
+
+// euler's pentagonal number theorem
+#[optimize_recursion]
+pub fn partitions(n: u64) -> i64 {
+    match n {
+        0 => 1,
+        _ => {
+            let mut sum = 0;
+            let mut k = 1;
+            while k * (3 * k - 1) / 2 <= n {
+                let sign = if k % 2 == 1 { 1 } else { -1 };
+                sum += sign * partitions(n - k * (3 * k - 1) / 2);
+                if k * (3 * k + 1) / 2 <= n {
+                    sum += sign * partitions(n - k * (3 * k + 1) / 2);
+                }
+                k += 1;
+            }
+            sum
+        }
+    }
+}
+
+// the table starts at the smallest constant
+#[optimize_recursion]
+pub fn shifted(n: i32) -> i64 {
+    match n {
+        5 => 2,
+        _ => (5..n).map(|i| shifted(i) + 1).sum::<i64>()
+    }
+}
+
+#[test]
+fn test_catalan() {
+    for n in 0..15 {
+        assert_eq!(catalan(n), catalan_base(n));
+        assert_eq!(catalan_loop(n as u32), catalan_base(n));
+    }
+    assert_eq!(catalan(35), 3_116_285_494_907_301_262);
+}
+
+#[test]
+fn test_partitions() {
+    let expected = [1, 1, 2, 3, 5, 7, 11, 15, 22, 30, 42];
+    for (n, p) in expected.iter().enumerate() {
+        assert_eq!(partitions(n as u64), *p);
+    }
+    assert_eq!(partitions(100), 190_569_292);
+}
+
+#[test]
+fn test_shifted() {
+    assert_eq!(shifted(5), 2);
+    assert_eq!(shifted(6), 3);
+    assert_eq!(shifted(7), 7);
+}
+
+#[test]
+#[should_panic(expected = "argument 4 lies below the smallest constant 5, the recursion leaves the domain of i32")]
+fn test_shifted_below_start() {
+    shifted(4);
+}
@@ -0,0 +1,103 @@
+use optimize_recursion::optimize_recursion;
+
+// minimal number of scalar multiplications for the matrices i..=j, matrix k has the size dims[k] x dims[k + 1]
+#[optimize_recursion]
+pub fn matrix_chain(dims: &[u64], i: usize, j: usize) -> u64 {
+    if i == j {
+        0
+    } else {
+        (i..j).map(|k| matrix_chain(dims, i, k) + matrix_chain(dims, k + 1, j) + dims[i] * dims[k + 1] * dims[j + 1])
+            .min()
+            .unwrap()
+    }
+}
+
+pub fn matrix_chain_base(dims: &[u64], i: usize, j: usize) -> u64 {
+    if i == j {
+        0
+    } else {
+        (i..j).map(|k| matrix_chain_base(dims, i, k) + matrix_chain_base(dims, k + 1, j) + dims[i] * dims[k + 1] * dims[j + 1])
+            .min()
+            .unwrap()
+    }
+}
+
+// expected search cost of an optimal binary search tree for the keys i..=j, the empty tree costs nothing
+#[optimize_recursion]
+pub fn optimal_bst(frequencies: &[u32], i: i32, j: i32) -> u32 {
+    if i > j {
+        return 0;
+    }
+    let weight: u32 = frequencies[i as usize..=j as usize].iter().sum();
+    let mut best = u32::MAX;
+    for root in i..=j {
+        best = best.min(optimal_bst(frequencies, i, root - 1) + optimal_bst(frequencies, root + 1, j));
+    }
+    best + weight
+}
+
+pub fn optimal_bst_base(frequencies: &[u32], i: i32, j: i32) -> u32 {
+    if i > j {
+        return 0;
+    }
+    let weight: u32 = frequencies[i as usize..=j as usize].iter().sum();
+    let mut best = u32::MAX;
+    for root in i..=j {
+        best = best.min(optimal_bst_base(frequencies, i, root - 1) + optimal_bst_base(frequencies, root + 1, j));
+    }
+    best + weight
+}
+
+// length of the longest palindromic subsequence of s[i..=j]
+#[optimize_recursion]
+pub fn palindrome(i: usize, j: usize, s: &[u8]) -> u32 {
+    if i == j {
+        1
+    } else if s[i] == s[j] {
+        if i + 1 == j { 2 } else { 2 + palindrome(i + 1, j - 1, s) }
+    } else {
+        palindrome(i + 1, j, s).max(palindrome(i, j - 1, s))
+    }
+}
+
+// both parameters move down, the calls do not get intervals inside n..=k, so it is memoized instead
+#[optimize_recursion(strategy = "auto")]
+pub fn binom(n: u64, k: u64) -> u64 {
+    match k {
+        0 => 1,
+        _ => if k == n { 1 } else { binom(n - 1, k - 1) + binom(n - 1, k) }
+    }
+}
+
+#[test]
+fn matrices() {
+    let dims = [40, 20, 30, 10, 30];
+    assert_eq!(matrix_chain(&dims, 0, 3), 26000);
+    assert_eq!(matrix_chain(&dims, 1, 2), matrix_chain_base(&dims, 1, 2));
+    let dims: Vec<u64> = (0..14).map(|x| (x * 7 + 3) % 11 + 1).collect();
+    assert_eq!(matrix_chain(&dims, 0, 12), matrix_chain_base(&dims, 0, 12));
+    // exponential for the recursion
+    let dims: Vec<u64> = (0..201).map(|x| (x * 7 + 3) % 11 + 1).collect();
+    assert!(matrix_chain(&dims, 0, 199) > 0);
+}
+
+#[test]
+fn search_tree() {
+    let frequencies = [34, 8, 50, 12, 7, 20, 3, 41];
+    assert_eq!(optimal_bst(&frequencies, 0, 2), 142);
+    assert_eq!(optimal_bst(&frequencies, 0, 7), optimal_bst_base(&frequencies, 0, 7));
+    assert_eq!(optimal_bst(&frequencies, 3, 2), 0);
+}
+
+#[test]
+fn palindromic_subsequence() {
+    let s = b"bbbabcbcab";
+    assert_eq!(palindrome(0, s.len() - 1, s), 7);
+    assert_eq!(palindrome(2, 2, s), 1);
+}
+
+#[test]
+fn not_an_interval() {
+    assert_eq!(binom(5, 2), 10);
+    assert_eq!(binom(60, 30), 118264581564861424);
+}
@@ -0,0 +1,53 @@
+use optimize_recursion::optimize_recursion;
+
+pub struct Model {
+    weights: Vec<u64>,
+    decay: u64,
+}
+
+impl Model {
+    #[optimize_recursion]
+    pub fn score(&self, n: usize) -> u64 {
+        match n {
+            0 => 1,
+            1 => 2,
+            _ => self.score(n - 1) * self.decay + self.score(n - 2) + self.weight(n)
+        }
+    }
+
+    pub fn score_base(&self, n: usize) -> u64 {
+        match n {
+            0 => 1,
+            1 => 2,
+            _ => self.score_base(n - 1) * self.decay + self.score_base(n - 2) + self.weight(n)
+        }
+    }
+
+    // tail recursive method
+    #[optimize_recursion]
+    pub fn total_weight(&self, n: usize, acc: u64) -> u64 {
+        match n {
+            0 => acc,
+            _ => self.total_weight(n - 1, acc + self.weight(n))
+        }
+    }
+
+    fn weight(&self, n: usize) -> u64 {
+        self.weights[n % self.weights.len()]
+    }
+}
+
+#[test]
+fn test_score() {
+    let model = Model { weights: vec![3, 1, 4, 1, 5], decay: 2 };
+    for n in 0..25 {
+        assert_eq!(model.score(n), model.score_base(n));
+    }
+}
+
+#[test]
+fn test_total_weight() {
+    let model = Model { weights: vec![3, 1, 4, 1, 5], decay: 2 };
+    assert_eq!(model.total_weight(5, 0), 1 + 4 + 1 + 5 + 3);
+    assert_eq!(model.total_weight(1_000_000, 0), 2_800_000);
+}
@@ -0,0 +1,201 @@
+use optimize_recursion::optimize_recursion;
+
+#[optimize_recursion]
+mod parity {
+    pub fn is_even(n: u64) -> bool {
+        match n {
+            0 => true,
+            _ => is_odd(n - 1)
+        }
+    }
+
+    pub fn is_odd(n: u64) -> bool {
+        match n {
+            0 => false,
+            _ => is_even(n - 1)
+        }
+    }
+}
+
+// a(n) counts the binary strings of length n without two consecutive ones ending in 0, b(n) the ones ending in 1
+#[optimize_recursion]
+mod coupled {
+    pub fn a(n: u32) -> u64 {
+        match n {
+            1 => 1,
+            _ => a(n - 1) + b(n - 1)
+        }
+    }
+
+    pub fn b(n: u32) -> u64 {
+        match n {
+            1 => 1,
+            _ => a(n - 1)
+        }
+    }
+}
+
+#[optimize_recursion]
+mod third_order {
+    pub fn x(n: i32) -> i64 {
+        match n {
+            1 => 1,
+            2 => 1,
+            3 => 1,
+            _ => x(n - 1) + y(n - 3)
+        }
+    }
+
+    pub fn y(n: i32) -> i64 {
+        match n {
+            1 => 0,
+            2 => 1,
+            _ => y(n - 1) + x(n - 2)
+        }
+    }
+}
+
+// consts of the module are step sizes and offsets like consts in a function body
+#[optimize_recursion]
+mod module_consts {
+    const STEP: u64 = 2;
+    const OFFSETS: [u64; 2] = [1, 2];
+
+    pub fn a(n: u64) -> u64 {
+        match n {
+            0 => 1,
+            1 => 1,
+            _ => OFFSETS.iter().map(|&k| b(n - k)).sum()
+        }
+    }
+
+    pub fn b(n: u64) -> u64 {
+        match n {
+            0 => 0,
+            1 => 2,
+            _ => a(n - STEP) + 1
+        }
+    }
+}
+
+// helper functions and types of the module are kept as they are, the values need no Default
+#[optimize_recursion]
+mod with_helpers {
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Pair {
+        pub low: u64,
+        pub high: u64,
+    }
+
+    pub fn weight(n: u64) -> u64 {
+        n % 3
+    }
+
+    pub fn up(n: u64) -> Pair {
+        match n {
+            0 => Pair { low: 0, high: 1 },
+            _ => Pair { low: down(n - 1).low + weight(n), high: down(n - 1).high }
+        }
+    }
+
+    pub fn down(n: u64) -> Pair {
+        match n {
+            0 => Pair { low: 1, high: 0 },
+            _ => Pair { low: up(n - 1).high, high: up(n - 1).low + up(n - 1).high }
+        }
+    }
+}
+
+pub fn up_base(n: u64) -> with_helpers::Pair {
+    match n {
+        0 => with_helpers::Pair { low: 0, high: 1 },
+        _ => with_helpers::Pair { low: down_base(n - 1).low + with_helpers::weight(n), high: down_base(n - 1).high }
+    }
+}
+
+pub fn down_base(n: u64) -> with_helpers::Pair {
+    match n {
+        0 => with_helpers::Pair { low: 1, high: 0 },
+        _ => with_helpers::Pair { low: up_base(n - 1).high, high: up_base(n - 1).low + up_base(n - 1).high }
+    }
+}
+
+pub fn a_base(n: u64) -> u64 {
+    match n {
+        0 => 1,
+        1 => 1,
+        _ => b_base(n - 1) + b_base(n - 2)
+    }
+}
+
+pub fn b_base(n: u64) -> u64 {
+    match n {
+        0 => 0,
+        1 => 2,
+        _ => a_base(n - 2) + 1
+    }
+}
+
+pub fn x_base(n: i32) -> i64 {
+    match n {
+        1 => 1,
+        2 => 1,
+        3 => 1,
+        _ => x_base(n - 1) + y_base(n - 3)
+    }
+}
+
+pub fn y_base(n: i32) -> i64 {
+    match n {
+        1 => 0,
+        2 => 1,
+        _ => y_base(n - 1) + x_base(n - 2)
+    }
+}
+
+#[test]
+fn test_parity() {
+    for n in 0..100 {
+        assert_eq!(parity::is_even(n), n % 2 == 0);
+        assert_eq!(parity::is_odd(n), n % 2 == 1);
+    }
+    assert!(parity::is_even(10_000_000));
+}
+
+#[test]
+fn test_coupled() {
+    // fibonacci numbers
+    assert_eq!(coupled::a(1) + coupled::b(1), 2);
+    assert_eq!(coupled::a(10) + coupled::b(10), 144);
+    assert_eq!(coupled::b(10), coupled::a(9));
+}
+
+#[test]
+fn test_with_helpers() {
+    for n in 0..20 {
+        assert_eq!(with_helpers::up(n), up_base(n));
+        assert_eq!(with_helpers::down(n), down_base(n));
+    }
+}
+
+#[test]
+fn test_module_consts() {
+    for n in 0..30 {
+        assert_eq!(module_consts::a(n), a_base(n));
+        assert_eq!(module_consts::b(n), b_base(n));
+    }
+}
+
+#[test]
+fn test_third_order() {
+    for n in 1..20 {
+        assert_eq!(third_order::x(n), x_base(n));
+        assert_eq!(third_order::y(n), y_base(n));
+    }
+}
+
+#[test]
+#[should_panic(expected = "argument 0 lies below the smallest constant 1, the recursion leaves the domain of i32")]
+fn test_third_order_below_start() {
+    third_order::x(0);
+}
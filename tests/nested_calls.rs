@@ -0,0 +1,60 @@
+use optimize_recursion::optimize_recursion;
+
+// McCarthy 91 function, the argument of the outer call is only known after the inner call
+#[optimize_recursion]
+pub fn mc91(n: i64) -> i64 {
+    if n > 100 {
+        n - 10
+    } else {
+        mc91(mc91(n + 11))
+    }
+}
+
+pub fn mc91_base(n: i64) -> i64 {
+    if n > 100 {
+        n - 10
+    } else {
+        mc91_base(mc91_base(n + 11))
+    }
+}
+
+// Takeuchi function
+#[optimize_recursion]
+pub fn tak(x: i32, y: i32, z: i32) -> i32 {
+    if y < x {
+        tak(tak(x - 1, y, z), tak(y - 1, z, x), tak(z - 1, x, y))
+    } else {
+        z
+    }
+}
+
+pub fn tak_base(x: i32, y: i32, z: i32) -> i32 {
+    if y < x {
+        tak_base(tak_base(x - 1, y, z), tak_base(y - 1, z, x), tak_base(z - 1, x, y))
+    } else {
+        z
+    }
+}
+
+#[test]
+fn mccarthy() {
+    for n in -20..200 {
+        assert_eq!(mc91(n), mc91_base(n));
+    }
+    // deep recursion for the original function
+    assert_eq!(mc91(-1_000_000), 91);
+}
+
+#[test]
+fn takeuchi() {
+    for x in 0..8 {
+        for y in 0..8 {
+            for z in 0..8 {
+                assert_eq!(tak(x, y, z), tak_base(x, y, z));
+            }
+        }
+    }
+    assert_eq!(tak(18, 12, 6), tak_base(18, 12, 6));
+    // exponential for the recursion
+    assert!((0..=40).contains(&tak(40, 20, 0)));
+}
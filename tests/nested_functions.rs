@@ -0,0 +1,58 @@
+use optimize_recursion::optimize_recursion;
+
+// the recursion is in the helper carrying the last two values, the function projects the first one
+#[optimize_recursion]
+pub fn foo_incremental(n: i64) -> i64 {
+    fn foo(n: i64) -> (i64, i64) {
+        match n {
+            0 => (0, 1000), // second argument is not relevant
+            1 => (1, 0),
+            _ => {
+                let r = foo(n - 1);
+                (r.0 - r.1, r.0)
+            }
+        }
+    }
+    foo(n).0
+}
+
+// statements around the helpers stay as they are
+#[optimize_recursion]
+pub fn fib_sum(n: u64) -> u64 {
+    let offset = 2;
+    fn fib(n: u64) -> u64 {
+        match n {
+            0 => 0,
+            1 => 1,
+            _ => fib(n - 1) + fib(n - 2)
+        }
+    }
+    fn double(x: u64) -> u64 {
+        2 * x
+    }
+    let total = fib(n + offset);
+    total + double(n)
+}
+
+pub fn foo_base(n: i64) -> i64 {
+    match n {
+        0 => 0,
+        1 => 1,
+        _ => foo_base(n - 1) - foo_base(n - 2)
+    }
+}
+
+#[test]
+fn projected_helper() {
+    for n in 0..30 {
+        assert_eq!(foo_incremental(n), foo_base(n));
+    }
+    assert_eq!(foo_incremental(1_000_000), foo_base(1_000_000 % 6));
+}
+
+#[test]
+fn surrounding_statements() {
+    assert_eq!(fib_sum(0), 1);
+    assert_eq!(fib_sum(10), 144 + 20);
+    assert_eq!(fib_sum(88), 2880067194370816120 + 176);
+}
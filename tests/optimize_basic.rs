@@ -7,34 +7,83 @@ pub fn foo(n: u32) -> u64 {
         1 => 1,
         2 => 2,
         3 => 3,
+        #[override_formula]
         51 => 0,
         _ => foo(n - 2) + foo(n - 4)
     }
 }
 
-// 4 could be computed and that would lead to a possible eureka tuple fill
+// 4 is computed at compile time and that leads to a possible eureka tuple fill
+#[optimize_recursion]
 pub fn more_needed(n: u64) -> u64 {
     match n {
         1 => 1,
         3 => 3,
         5 => 5,
+        #[override_formula]
         6 => 6,
         _ => more_needed(n - 1) + more_needed(n - 3)
     }
 }
 
+// only the values next to a constant are computed at compile time, not the ones up to 100000000000
+#[optimize_recursion]
+pub fn sparse(n: u64) -> u64 {
+    match n {
+        0 => 0,
+        1 => 1,
+        100000000000 => 5,
+        _ => sparse(n - 1) + sparse(n - 2)
+    }
+}
+
+fn last_digits(v: u64) -> u64 {
+    v % 1000
+}
+
+// the recursive calls are arguments of another function
+#[optimize_recursion]
+pub fn wrapped(n: u64) -> u64 {
+    match n {
+        0 => 0,
+        1 => 1,
+        _ => last_digits(wrapped(n - 1) + wrapped(n - 2))
+    }
+}
+
+pub fn wrapped_base(n: u64) -> u64 {
+    match n {
+        0 => 0,
+        1 => 1,
+        _ => last_digits(wrapped_base(n - 1) + wrapped_base(n - 2))
+    }
+}
+
 // this has a special constant which needs to be considered
 #[optimize_recursion]
 pub fn constant_contradicts(n: u64) -> u64 {
     match n {
         0 => 0,
         1 => 1,
+        #[override_formula]
         5 => 1,
         _ => constant_contradicts(n - 1) + constant_contradicts(n - 2)
     }
 }
 
 
+// like in a normal match only the first arm for 1 is used
+#[optimize_recursion]
+#[allow(deprecated)]
+pub fn duplicate_constant(n: u64) -> u64 {
+    match n {
+        0 => 0,
+        1 => 1,
+        1 => 5,
+        _ => duplicate_constant(n - 1) + duplicate_constant(n - 2)
+    }
+}
+
 #[optimize_recursion]
 pub fn fib_match(n: u64) -> u64 {
     match n {
@@ -45,6 +94,33 @@ pub fn fib_match(n: u64) -> u64 {
     }
 }
 
+// every u32 argument is covered by a constant or a branch
+#[optimize_recursion(total)]
+pub fn fib_total(n: u32) -> u64 {
+    match n {
+        0 => 0,
+        1 => 1,
+        _ => fib_total(n - 1) + fib_total(n - 2)
+    }
+}
+
+#[optimize_recursion(overflow_check)]
+pub fn fib_checked(n: u64) -> u64 {
+    match n {
+        0 => 0,
+        1 => 1,
+        _ => fib_checked(n - 1) + fib_checked(n - 2)
+    }
+}
+
+#[optimize_recursion(overflow_check)]
+pub fn digits(n: u64) -> u8 {
+    match n {
+        0 => 0,
+        _ => ((digits(n - 1) as u64 + n) % 10) as u8
+    }
+}
+
 #[optimize_recursion]
 pub fn evil(n: u64) -> u64 {
     match n {
@@ -89,6 +165,7 @@ pub fn another2(n: u64) -> u64 {
 }
 
 // not possible
+#[allow(clippy::manual_is_multiple_of)]
 pub fn ifelse(n: u64) -> u64 {
     match n {
         0 => 0,
@@ -96,6 +173,7 @@ pub fn ifelse(n: u64) -> u64 {
     }
 }
 
+#[allow(clippy::manual_is_multiple_of)]
 pub fn evil_result(n: u64) -> u64 {
     if n == 100 { return 1; }
     if n == 102 { return 2; }
@@ -116,6 +194,7 @@ pub fn evil_result(n: u64) -> u64 {
 }
 
 
+#[allow(clippy::manual_is_multiple_of, clippy::match_single_binding)]
 pub fn foo_result(n: u32) -> u64 {
     if n == 0 { return 0; }
     if n == 1 { return 1; }
@@ -154,6 +233,7 @@ pub fn foo_result(n: u32) -> u64 {
     panic!("result for argument not defined");
 }
 
+#[allow(clippy::identity_op, clippy::manual_is_multiple_of, clippy::modulo_one)]
 pub fn fib_result(n: u64) -> u64 {
     if n == 0 { return 0; }
     if n == 1 { return 1; }
@@ -178,6 +258,12 @@ pub fn test_fib_match() {
     assert_eq!(fib_match(2), 1);
 }
 
+#[test]
+pub fn test_duplicate_constant() {
+    assert_eq!(duplicate_constant(1), 1);
+    assert_eq!(duplicate_constant(10), 55);
+}
+
 #[test]
 pub fn test_fib_result() {
     assert_eq!(fib_result(10), 55);
@@ -185,6 +271,32 @@ pub fn test_fib_result() {
     assert_eq!(fib_result(2), 1);
 }
 
+#[test]
+pub fn test_fib_total() {
+    assert_eq!(fib_total(0), 0);
+    assert_eq!(fib_total(10), 55);
+}
+
+#[test]
+pub fn test_fib_checked() {
+    assert_eq!(fib_checked(93), 12200160415121876738);
+}
+
+#[test]
+#[should_panic(expected = "fib_checked overflows u64 for arguments >= 94")]
+pub fn test_fib_checked_overflow() {
+    fib_checked(94);
+}
+
+#[test]
+pub fn test_digits_widening_cast() {
+    let mut expected = 0u8;
+    for n in 1..=1000u64 {
+        expected = ((expected as u64 + n) % 10) as u8;
+    }
+    assert_eq!(digits(1000), expected);
+}
+
 #[test]
 pub fn test_evil() {
     assert_eq!(evil(98), 5);
@@ -193,6 +305,12 @@ pub fn test_evil() {
     assert_eq!(evil_result(90), 41);
 }
 
+#[test]
+#[should_panic(expected = "argument 101 does not reach a constant, the recursion steps above the constants and leaves the domain of u64")]
+pub fn test_evil_diverges() {
+    evil(101);
+}
+
 #[test]
 pub fn test_two_starts() {
     assert_eq!(two_starts(10), 5);
@@ -210,6 +328,34 @@ pub fn test_another2() {
     // assert_eq!(another2(1004), 70);
 }
 
+#[test]
+pub fn test_wrapped() {
+    for i in 0..25 {
+        assert_eq!(wrapped(i), wrapped_base(i));
+    }
+}
+
+#[test]
+pub fn test_sparse() {
+    assert_eq!(sparse(10), 55);
+    assert_eq!(sparse(100000000000), 5);
+}
+
+#[test]
+pub fn test_more_needed() {
+    assert_eq!(more_needed(4), 4);
+    assert_eq!(more_needed(5), 5);
+    assert_eq!(more_needed(7), 10);
+    assert_eq!(more_needed(9), 21);
+}
+
+#[test]
+pub fn test_constant_contradicts() {
+    assert_eq!(constant_contradicts(4), 3);
+    assert_eq!(constant_contradicts(5), 1);
+    assert_eq!(constant_contradicts(6), 4);
+}
+
 #[test]
 pub fn test_foo() {
     for i in 0..20 {
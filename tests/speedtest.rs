@@ -39,6 +39,7 @@ pub fn foo_optimize(n: i64) -> i64 {
     }
 }
 
+#[allow(clippy::identity_op, clippy::match_single_binding, clippy::modulo_one)]
 pub fn foo_optimize_result(n: i64) -> i64 {
     if n == 0 { return 0; }
     if n == 1 { return 1; }
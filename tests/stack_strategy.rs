@@ -0,0 +1,61 @@
+use optimize_recursion::optimize_recursion;
+
+#[optimize_recursion(strategy = "stack")]
+pub fn ackermann(m: u64, n: u64) -> u64 {
+    match (m, n) {
+        (0, n) => n + 1,
+        (m, 0) => ackermann(m - 1, 1),
+        (m, n) => ackermann(m - 1, ackermann(m, n - 1))
+    }
+}
+
+pub fn ackermann_base(m: u64, n: u64) -> u64 {
+    match (m, n) {
+        (0, n) => n + 1,
+        (m, 0) => ackermann_base(m - 1, 1),
+        (m, n) => ackermann_base(m - 1, ackermann_base(m, n - 1))
+    }
+}
+
+#[optimize_recursion(strategy = "stack")]
+pub fn mc91(n: i64) -> i64 {
+    if n > 100 {
+        n - 10
+    } else {
+        mc91(mc91(n + 11))
+    }
+}
+
+// far too deep for the call stack
+#[optimize_recursion(strategy = "stack")]
+pub fn triangular(n: u64) -> u64 {
+    if n == 0 {
+        return 0;
+    }
+    n + triangular(n - 1)
+}
+
+#[test]
+fn test_ackermann() {
+    for m in 0..3 {
+        for n in 0..10 {
+            assert_eq!(ackermann(m, n), ackermann_base(m, n));
+        }
+    }
+    assert_eq!(ackermann(3, 10), 8189);
+}
+
+#[test]
+fn test_mc91() {
+    for n in -100..=100 {
+        assert_eq!(mc91(n), 91);
+    }
+    assert_eq!(mc91(150), 140);
+}
+
+#[test]
+fn test_triangular() {
+    assert_eq!(triangular(0), 0);
+    assert_eq!(triangular(10), 55);
+    assert_eq!(triangular(1_000_000), 500_000_500_000);
+}
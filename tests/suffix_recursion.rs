@@ -0,0 +1,92 @@
+use optimize_recursion::optimize_recursion;
+
+// ways to decode a digit string with 1 = a, ..., 26 = z
+#[optimize_recursion]
+pub fn decode_ways(s: &[u8]) -> u64 {
+    match s.len() {
+        0 => 1,
+        _ => (if s[0] != b'0' { decode_ways(&s[1..]) } else { 0 })
+            + if s.len() >= 2 && (s[0] == b'1' || (s[0] == b'2' && s[1] <= b'6')) { decode_ways(&s[2..]) } else { 0 }
+    }
+}
+
+pub fn decode_ways_base(s: &[u8]) -> u64 {
+    match s.len() {
+        0 => 1,
+        _ => (if s[0] != b'0' { decode_ways_base(&s[1..]) } else { 0 })
+            + if s.len() >= 2 && (s[0] == b'1' || (s[0] == b'2' && s[1] <= b'6')) { decode_ways_base(&s[2..]) } else { 0 }
+    }
+}
+
+// number of vowels, one call per character
+#[optimize_recursion]
+pub fn vowels(s: &str) -> usize {
+    match s.len() {
+        0 => 0,
+        _ => s.starts_with(['a', 'e', 'i', 'o', 'u']) as usize + vowels(&s[1..])
+    }
+}
+
+// elements at even positions, only lengths of the same parity are reached
+#[optimize_recursion]
+pub fn every_other(s: &[u32]) -> u32 {
+    match s.len() {
+        1 => 1,
+        _ => 1 + every_other(&s[2..])
+    }
+}
+
+// pairs of equal neighbours, the last character alone has none
+#[optimize_recursion]
+pub fn equal_pairs(s: &[i32]) -> u32 {
+    match s.len() {
+        1 => 0,
+        _ => {
+            if s[0] == s[1] {
+                return 1 + equal_pairs(&s[1..]);
+            }
+            equal_pairs(&s[1..])
+        }
+    }
+}
+
+#[test]
+fn decode() {
+    for s in ["", "0", "1", "12", "226", "06", "11106", "1111111111", "2611055971756562"] {
+        assert_eq!(decode_ways(s.as_bytes()), decode_ways_base(s.as_bytes()));
+    }
+    // the recursion takes exponential time and would overflow the stack
+    let ones = vec![b'1'; 90];
+    assert_eq!(decode_ways(&ones), 4660046610375530309);
+}
+
+#[test]
+fn string_suffixes() {
+    assert_eq!(vowels(""), 0);
+    assert_eq!(vowels("optimize recursion"), 8);
+    assert_eq!(vowels(&"ab".repeat(1_000_000)), 1_000_000);
+}
+
+#[test]
+fn early_return() {
+    assert_eq!(equal_pairs(&[1, 1, 2, 2, 2, 3]), 3);
+    assert_eq!(equal_pairs(&[5]), 0);
+}
+
+#[test]
+#[should_panic(expected = "length 0 lies below the smallest constant 1, the recursion indexes past the end of the slice")]
+fn below_smallest_constant() {
+    equal_pairs(&[]);
+}
+
+#[test]
+fn same_parity() {
+    assert_eq!(every_other(&[0; 7]), 4);
+    assert_eq!(every_other(&[0; 1_000_001]), 500_001);
+}
+
+#[test]
+#[should_panic(expected = "every_other(&s[..]) needs a suffix of length 0 which is shorter than the smallest constant")]
+fn suffix_below_smallest_constant() {
+    every_other(&[0; 4]);
+}
@@ -0,0 +1,112 @@
+use optimize_recursion::optimize_recursion;
+
+#[optimize_recursion]
+pub fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+#[optimize_recursion]
+pub fn fact_acc(n: u64, acc: u64) -> u64 {
+    match n {
+        0 => acc,
+        _ => fact_acc(n - 1, acc * n)
+    }
+}
+
+// early return and a parameter which is changed before the tail call
+#[optimize_recursion]
+pub fn count_down(mut n: u64, steps: u64) -> u64 {
+    if n == 0 {
+        return steps;
+    }
+    for _ in 0..2 {
+        if n.is_multiple_of(2) {
+            return count_down(n / 2, steps + 1);
+        }
+    }
+    n -= 1;
+    count_down(n, steps + 1)
+}
+
+pub fn count_down_base(mut n: u64, steps: u64) -> u64 {
+    if n == 0 {
+        return steps;
+    }
+    for _ in 0..2 {
+        if n.is_multiple_of(2) {
+            return count_down_base(n / 2, steps + 1);
+        }
+    }
+    n -= 1;
+    count_down_base(n, steps + 1)
+}
+
+// too deep for the call stack
+#[optimize_recursion]
+pub fn is_zero_after(n: u64, k: u64) -> bool {
+    match n {
+        0 => k == 0,
+        _ => is_zero_after(n - 1, if k == 0 { 0 } else { k - 1 })
+    }
+}
+
+// the body shadows the parameter acc, the tail call still passes on the new value
+#[optimize_recursion]
+pub fn sum_to(n: u64, acc: u64) -> u64 {
+    if n == 0 {
+        acc
+    } else {
+        let mut acc = acc;
+        acc += n;
+        sum_to(n - 1, acc)
+    }
+}
+
+#[optimize_recursion]
+pub fn sum_to_shadowed(n: u64, acc: u64) -> u64 {
+    if n == 0 {
+        return acc;
+    }
+    let acc = acc + n;
+    let n = n - 1;
+    sum_to_shadowed(n, acc)
+}
+
+#[test]
+fn test_gcd() {
+    assert_eq!(gcd(12, 18), 6);
+    assert_eq!(gcd(17, 5), 1);
+    assert_eq!(gcd(0, 7), 7);
+    assert_eq!(gcd(7, 0), 7);
+}
+
+#[test]
+fn test_fact_acc() {
+    assert_eq!(fact_acc(0, 1), 1);
+    assert_eq!(fact_acc(5, 1), 120);
+    assert_eq!(fact_acc(20, 1), 2432902008176640000);
+}
+
+#[test]
+fn test_count_down() {
+    for n in 0..500 {
+        assert_eq!(count_down(n, 0), count_down_base(n, 0));
+    }
+}
+
+#[test]
+fn test_is_zero_after() {
+    assert!(is_zero_after(100_000_000, 5));
+    assert!(!is_zero_after(3, 5));
+}
+
+#[test]
+fn test_shadowed_parameters() {
+    assert_eq!(sum_to(4, 0), 10);
+    assert_eq!(sum_to_shadowed(4, 0), 10);
+    assert_eq!(sum_to_shadowed(1_000_000, 0), 500_000_500_000);
+}
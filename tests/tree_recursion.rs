@@ -0,0 +1,85 @@
+use optimize_recursion::optimize_recursion;
+
+pub enum Tree {
+    Leaf,
+    Node(Box<Tree>, Box<Tree>),
+}
+
+use Tree::{Leaf, Node};
+
+#[optimize_recursion]
+pub fn depth(t: &Tree) -> u64 {
+    match t {
+        Leaf => 0,
+        Node(l, r) => 1 + std::cmp::max(depth(l), depth(r))
+    }
+}
+
+#[optimize_recursion]
+pub fn leaves(t: &Tree) -> u64 {
+    match t {
+        Tree::Leaf => 1,
+        Tree::Node(l, r) => leaves(l) + leaves(r)
+    }
+}
+
+pub enum Expression {
+    Number(i64),
+    Sum { left: Box<Expression>, right: Box<Expression> },
+    Product { left: Box<Expression>, right: Box<Expression> },
+    Negation(Box<Expression>),
+}
+
+#[optimize_recursion]
+pub fn evaluate(e: &Expression) -> i64 {
+    match e {
+        Expression::Number(x) => *x,
+        Expression::Sum { left, right } => evaluate(left) + evaluate(right),
+        Expression::Product { left, right } => {
+            let left = evaluate(left);
+            if left == 0 {
+                return 0;
+            }
+            left * evaluate(right)
+        }
+        Expression::Negation(e) => -evaluate(e)
+    }
+}
+
+/// a tree which has only left children
+fn degenerate(nodes: usize) -> Tree {
+    let mut t = Leaf;
+    for _ in 0..nodes {
+        t = Node(Box::new(t), Box::new(Leaf));
+    }
+    t
+}
+
+#[test]
+fn small_trees() {
+    let t = Node(Box::new(Leaf), Box::new(Node(Box::new(Leaf), Box::new(Leaf))));
+    assert_eq!(depth(&t), 2);
+    assert_eq!(leaves(&t), 3);
+    assert_eq!(depth(&Leaf), 0);
+}
+
+#[test]
+fn deep_tree() {
+    let t = degenerate(1_000_000);
+    assert_eq!(depth(&t), 1_000_000);
+    assert_eq!(leaves(&t), 1_000_001);
+    // the generated drop glue of the tree is recursive and would overflow the stack
+    std::mem::forget(t);
+}
+
+#[test]
+fn expression() {
+    use Expression::*;
+    let e = Sum {
+        left: Box::new(Number(4)),
+        right: Box::new(Product { left: Box::new(Negation(Box::new(Number(3)))), right: Box::new(Number(5)) }),
+    };
+    assert_eq!(evaluate(&e), -11);
+    let zero = Product { left: Box::new(Number(0)), right: Box::new(Number(7)) };
+    assert_eq!(evaluate(&zero), 0);
+}
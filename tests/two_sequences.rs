@@ -0,0 +1,82 @@
+use optimize_recursion::optimize_recursion;
+
+#[optimize_recursion]
+pub fn lcs(a: &[u8], b: &[u8]) -> usize {
+    if a.is_empty() || b.is_empty() {
+        0
+    } else if a[0] == b[0] {
+        1 + lcs(&a[1..], &b[1..])
+    } else {
+        lcs(&a[1..], b).max(lcs(a, &b[1..]))
+    }
+}
+
+pub fn lcs_base(a: &[u8], b: &[u8]) -> usize {
+    if a.is_empty() || b.is_empty() {
+        0
+    } else if a[0] == b[0] {
+        1 + lcs_base(&a[1..], &b[1..])
+    } else {
+        lcs_base(&a[1..], b).max(lcs_base(a, &b[1..]))
+    }
+}
+
+#[optimize_recursion]
+pub fn edit_distance(a: &[char], b: &[char]) -> u32 {
+    if a.is_empty() {
+        return b.len() as u32;
+    }
+    if b.is_empty() {
+        return a.len() as u32;
+    }
+    let substitution = edit_distance(&a[1..], &b[1..]) + (a[0] != b[0]) as u32;
+    let deletion = edit_distance(&a[1..], b) + 1;
+    let insertion = edit_distance(a, &b[1..]) + 1;
+    substitution.min(deletion).min(insertion)
+}
+
+// the first slice is consumed in pairs
+#[optimize_recursion]
+pub fn pair_matches(a: &[i32], b: &[i32]) -> u64 {
+    match (a, b) {
+        ([x, y, ..], [z, ..]) if x + y == *z => 1 + pair_matches(&a[2..], &b[1..]),
+        ([_, _, ..], [_, ..]) => pair_matches(&a[2..], b) + pair_matches(a, &b[1..]),
+        _ => 0
+    }
+}
+
+pub fn pair_matches_base(a: &[i32], b: &[i32]) -> u64 {
+    match (a, b) {
+        ([x, y, ..], [z, ..]) if x + y == *z => 1 + pair_matches_base(&a[2..], &b[1..]),
+        ([_, _, ..], [_, ..]) => pair_matches_base(&a[2..], b) + pair_matches_base(a, &b[1..]),
+        _ => 0
+    }
+}
+
+#[test]
+fn longest_common_subsequence() {
+    for (a, b) in [("", "abc"), ("abc", ""), ("abcde", "ace"), ("abc", "def"), ("aggtab", "gxtxayb")] {
+        assert_eq!(lcs(a.as_bytes(), b.as_bytes()), lcs_base(a.as_bytes(), b.as_bytes()));
+    }
+    // exponential for the recursion
+    let a = "ab".repeat(500);
+    let b = "ba".repeat(500);
+    assert_eq!(lcs(a.as_bytes(), b.as_bytes()), 999);
+}
+
+#[test]
+fn levenshtein() {
+    let chars = |s: &str| s.chars().collect::<Vec<char>>();
+    assert_eq!(edit_distance(&chars("kitten"), &chars("sitting")), 3);
+    assert_eq!(edit_distance(&chars("flaw"), &chars("lawn")), 2);
+    assert_eq!(edit_distance(&chars(""), &chars("abc")), 3);
+    assert_eq!(edit_distance(&chars("größe"), &chars("grosse")), 3);
+}
+
+#[test]
+fn offset_two() {
+    let a = [1, 2, 3, 4, 2, 2, 1, 1];
+    let b = [3, 7, 4, 2];
+    assert_eq!(pair_matches(&a, &b), pair_matches_base(&a, &b));
+    assert_eq!(pair_matches(&a[1..], &b), pair_matches_base(&a[1..], &b));
+}
@@ -0,0 +1,13 @@
+use optimize_recursion::optimize_recursion;
+
+const COINS: [u32; 3] = [1, 5, 10];
+
+#[optimize_recursion]
+pub fn change(n: u32) -> u64 {
+    match n {
+        0 => 1,
+        _ => COINS.iter().map(|&c| if n >= c { change(n - c) } else { 0 }).sum()
+    }
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+use optimize_recursion::optimize_recursion;
+
+pub struct Node {
+    children: Vec<Node>,
+}
+
+#[optimize_recursion(strategy = "stack")]
+pub fn size(node: &Node) -> usize {
+    let mut total = 1;
+    for child in &node.children {
+        total += size(child);
+    }
+    total
+}
+
+fn main() {}
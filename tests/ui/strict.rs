@@ -0,0 +1,13 @@
+use optimize_recursion::optimize_recursion;
+
+#[optimize_recursion(strict)]
+pub fn duplicate_constant(n: u64) -> u64 {
+    match n {
+        0 => 0,
+        1 => 1,
+        1 => 2,
+        _ => duplicate_constant(n - 1) + duplicate_constant(n - 2)
+    }
+}
+
+fn main() {}
@@ -0,0 +1,30 @@
+#![deny(deprecated)]
+use optimize_recursion::optimize_recursion;
+
+#[optimize_recursion]
+pub fn constant_contradicts(n: u64) -> u64 {
+    match n {
+        0 => 0,
+        1 => 1,
+        5 => 1,
+        _ => constant_contradicts(n - 1) + constant_contradicts(n - 2)
+    }
+}
+
+#[optimize_recursion(strategy = "auto", verbose)]
+pub fn collatz_steps(n: u64) -> u64 {
+    match n {
+        1 => 0,
+        _ => 1 + if n % 2 == 0 { collatz_steps(n / 2) } else { collatz_steps(3 * n + 1) }
+    }
+}
+
+#[optimize_recursion(verbose)]
+pub fn every_third(n: u32) -> u64 {
+    match n {
+        0 => 1,
+        _ => every_third(n - 3) + 1
+    }
+}
+
+fn main() {}